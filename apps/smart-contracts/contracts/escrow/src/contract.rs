@@ -1,19 +1,38 @@
-use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, Map, String, Symbol, Val, Vec};
+use soroban_sdk::{
+    contract, contractimpl, Address, BytesN, Env, Error, Map, String, Symbol, Val, Vec,
+};
 
-use crate::core::{DisputeManager, EscrowManager, MilestoneManager};
+use crate::core::validators::milestone::validate_milestone_index;
+use crate::core::{
+    AppealManager, DepositManager, DisputeManager, EscrowManager, MilestoneManager,
+    ProposalManager, ProtocolManager, ResolutionOutcome, RoleManager, UpgradeManager,
+    CONTRACT_VERSION,
+};
 use crate::error::ContractError;
 use crate::events::handler::{
-    ChgEsc, DisEsc, DisputeResolved, EscrowDisputed, ExtTtlEvt, FundEsc, InitEsc,
-    MilestoneApproved, MilestoneStatusChanged,
+    ChgEsc, DisEsc, DisputeResolved, DisputeStatementSubmitted, DisputeVoteCast, DustSwept,
+    EscrowCancelled, EscrowDisputed, EscrowMigrated, EscrowRefunded, EscrowUpdateAccepted,
+    EscrowUpdateProposed, EscrowUpgraded, EvidenceSubmitted, ExtTtlEvt, FundEsc, FundsTransferred,
+    InitEsc, MilestoneApprovalAdded, MilestoneApprovalWithdrawn, MilestoneApproved,
+    MilestoneAutoApproved, MilestoneDisputeResolved, MilestoneDisputed, MilestoneRejected,
+    MilestoneReleased, MilestoneStatusChanged, MilestoneTtlExtended, PayoutBreakdown,
+    ProtocolConfigUpdated, ResolutionAppealed, ResolutionPosted, RoleTransferred,
+};
+use crate::storage::types::{
+    AddressBalance, Deposit, DisputeRecord, DisputeTarget, DisputeVoting, Escrow,
+    EscrowUpdateProposal, Evidence, EvidenceSubmission, MilestoneStatus, PayoutQuote,
+    PendingResolution, ProtocolConfig, Role, TransferKind,
 };
-use crate::storage::types::{AddressBalance, Escrow};
 
 #[contract]
 pub struct EscrowContract;
 
 #[contractimpl]
 impl EscrowContract {
-    pub fn __constructor() {}
+    /// The protocol admin and treasury are set once, at deployment, and the admin signs it.
+    pub fn __constructor(e: Env, admin: Address, treasury: Address) {
+        ProtocolManager::initialize(&e, &admin, &treasury);
+    }
 
     pub fn deploy(
         env: Env,
@@ -41,10 +60,12 @@ impl EscrowContract {
     // Escrow /////
     ////////////////////////
 
-    pub fn initialize_escrow(e: &Env, escrow_properties: Escrow) -> Result<Escrow, ContractError> {
+    pub fn initialize_escrow(e: &Env, escrow_properties: Escrow) -> Result<Escrow, Error> {
         let initialized_escrow = EscrowManager::initialize_escrow(e, escrow_properties)?;
         InitEsc {
-            escrow: initialized_escrow.clone(),
+            engagement_id: initialized_escrow.engagement_id.clone(),
+            amount: initialized_escrow.amount,
+            milestone_count: initialized_escrow.milestones.len(),
         }
         .publish(e);
         Ok(initialized_escrow)
@@ -55,19 +76,85 @@ impl EscrowContract {
         signer: Address,
         expected_escrow: Escrow,
         amount: i128,
-    ) -> Result<(), ContractError> {
-        EscrowManager::fund_escrow(e, &signer, &expected_escrow, amount)?;
-        FundEsc { signer, amount }.publish(e);
+    ) -> Result<(), Error> {
+        let escrow = EscrowManager::fund_escrow(e, &signer, &expected_escrow, amount)?;
+        FundEsc {
+            engagement_id: escrow.engagement_id,
+            signer,
+            amount,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    pub fn release_funds(e: &Env, release_signer: Address) -> Result<(), Error> {
+        let (escrow, quote) = EscrowManager::release_funds(e, &release_signer)?;
+        publish_payout(e, &escrow, &quote)?;
+        DisEsc {
+            engagement_id: escrow.engagement_id,
+            release_signer,
+        }
+        .publish(e);
         Ok(())
     }
 
-    pub fn release_funds(
+    pub fn release_milestone_funds(
         e: &Env,
+        milestone_index: i128,
         release_signer: Address,
-        trustless_work_address: Address,
-    ) -> Result<(), ContractError> {
-        EscrowManager::release_funds(e, &release_signer, &trustless_work_address)?;
-        DisEsc { release_signer }.publish(e);
+    ) -> Result<(), Error> {
+        let (escrow, quote) =
+            EscrowManager::release_milestone_funds(e, milestone_index, &release_signer)?;
+        publish_payout(e, &escrow, &quote)?;
+        let index = validate_milestone_index(&escrow, milestone_index)?;
+        let milestone = escrow.milestones.get(index).unwrap();
+        MilestoneReleased {
+            engagement_id: escrow.engagement_id.clone(),
+            release_signer,
+            milestone_index,
+            amount: milestone.amount,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    pub fn cancel_escrow(
+        e: Env,
+        platform_address: Address,
+        approver: Address,
+    ) -> Result<(), Error> {
+        let (escrow, refunds) = EscrowManager::cancel_escrow(&e, &platform_address, &approver)?;
+        for refund in refunds.iter() {
+            EscrowRefunded {
+                engagement_id: escrow.engagement_id.clone(),
+                depositor: refund.depositor,
+                amount: refund.amount,
+            }
+            .publish(&e);
+        }
+        EscrowCancelled {
+            engagement_id: escrow.engagement_id,
+            platform: platform_address,
+            approver,
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    pub fn quote_release(e: Env) -> Result<PayoutQuote, Error> {
+        EscrowManager::quote_release(&e)
+    }
+
+    pub fn refund(e: &Env) -> Result<(), Error> {
+        let (escrow, refunds) = EscrowManager::refund(e)?;
+        for refund in refunds.iter() {
+            EscrowRefunded {
+                engagement_id: escrow.engagement_id.clone(),
+                depositor: refund.depositor,
+                amount: refund.amount,
+            }
+            .publish(e);
+        }
         Ok(())
     }
 
@@ -75,36 +162,97 @@ impl EscrowContract {
         e: &Env,
         plataform_address: Address,
         escrow_properties: Escrow,
-    ) -> Result<Escrow, ContractError> {
-        let updated_escrow = EscrowManager::change_escrow_properties(
+    ) -> Result<Escrow, Error> {
+        let previous_escrow = EscrowManager::change_escrow_properties(
             e,
             &plataform_address,
             escrow_properties.clone(),
         )?;
         ChgEsc {
+            engagement_id: previous_escrow.engagement_id.clone(),
             platform: plataform_address,
-            engagement_id: escrow_properties.engagement_id.clone(),
-            new_escrow_properties: updated_escrow.clone(),
+            changes: EscrowManager::get_changes(e, &previous_escrow, &escrow_properties),
         }
         .publish(e);
-        Ok(updated_escrow)
+        Ok(escrow_properties)
     }
 
-    pub fn get_escrow(e: &Env) -> Result<Escrow, ContractError> {
+    /// Stages an update that only takes effect once the approver and the service provider
+    /// have both called `accept_escrow_update`. Required when the escrow holds funds or the
+    /// update changes the approver or the service provider.
+    pub fn propose_escrow_update(
+        e: Env,
+        platform_address: Address,
+        escrow_properties: Escrow,
+    ) -> Result<(), Error> {
+        let (escrow, proposal) =
+            ProposalManager::propose_escrow_update(&e, &platform_address, escrow_properties)?;
+        EscrowUpdateProposed {
+            engagement_id: escrow.engagement_id.clone(),
+            platform: platform_address,
+            changes: EscrowManager::get_changes(&e, &escrow, &proposal.escrow),
+            expires_at: proposal.expires_at,
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    pub fn accept_escrow_update(e: Env, signer: Address) -> Result<(), Error> {
+        let (escrow, proposal, applied) = ProposalManager::accept_escrow_update(&e, &signer)?;
+        EscrowUpdateAccepted {
+            engagement_id: escrow.engagement_id.clone(),
+            signer,
+        }
+        .publish(&e);
+        if applied {
+            ChgEsc {
+                engagement_id: escrow.engagement_id.clone(),
+                platform: proposal.proposer,
+                changes: EscrowManager::get_changes(&e, &escrow, &proposal.escrow),
+            }
+            .publish(&e);
+        }
+        Ok(())
+    }
+
+    pub fn get_escrow_update_proposal(e: Env) -> Result<EscrowUpdateProposal, Error> {
+        ProposalManager::get_update_proposal(&e)
+    }
+
+    /// Hands a single role to a new address. The current holder signs, together with the
+    /// platform when the role controls where funds go.
+    pub fn transfer_role(e: Env, role: Role, new_address: Address) -> Result<(), Error> {
+        let (escrow, previous_holder) = RoleManager::transfer_role(&e, role, &new_address)?;
+        RoleTransferred {
+            engagement_id: escrow.engagement_id,
+            role,
+            previous_holder,
+            new_holder: new_address,
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    pub fn get_escrow(e: &Env) -> Result<Escrow, Error> {
         EscrowManager::get_escrow(e)
     }
 
-    pub fn get_escrow_by_contract_id(
-        e: &Env,
-        contract_id: Address,
-    ) -> Result<Escrow, ContractError> {
+    pub fn get_escrow_by_contract_id(e: &Env, contract_id: Address) -> Result<Escrow, Error> {
         EscrowManager::get_escrow_by_contract_id(e, &contract_id)
     }
 
+    pub fn get_deposits(e: &Env) -> Vec<Deposit> {
+        DepositManager::get_deposits(e)
+    }
+
+    pub fn get_deposit(e: &Env, depositor: Address) -> i128 {
+        DepositManager::get_deposit(e, &depositor)
+    }
+
     pub fn get_multiple_escrow_balances(
         e: &Env,
         addresses: Vec<Address>,
-    ) -> Result<Vec<AddressBalance>, ContractError> {
+    ) -> Result<Vec<AddressBalance>, Error> {
         EscrowManager::get_multiple_escrow_balances(e, addresses)
     }
 
@@ -116,18 +264,15 @@ impl EscrowContract {
         e: &Env,
         platform_address: Address,
         ledgers_to_extend: u32,
-    ) -> Result<(), ContractError> {
+    ) -> Result<(), Error> {
         platform_address.require_auth();
 
         let escrow = EscrowManager::get_escrow(e)?;
         if platform_address != escrow.roles.platform_address {
-            return Err(ContractError::OnlyPlatformAddressExecuteThisFunction);
+            return Err(ContractError::OnlyPlatformAddressExecuteThisFunction.into());
         }
 
-        let min_ledgers = 1u32;
-        e.storage()
-            .instance()
-            .extend_ttl(min_ledgers, ledgers_to_extend);
+        EscrowManager::extend_ttl(e, ledgers_to_extend, ledgers_to_extend);
 
         ExtTtlEvt {
             platform: platform_address,
@@ -138,6 +283,86 @@ impl EscrowContract {
         Ok(())
     }
 
+    /// Extends the TTL of one milestone's entries: the milestone itself, its evidence log,
+    /// approvals, submission time and dispute state.
+    pub fn extend_milestone_ttl(
+        e: &Env,
+        platform_address: Address,
+        milestone_index: i128,
+        ledgers_to_extend: u32,
+    ) -> Result<(), Error> {
+        platform_address.require_auth();
+
+        let escrow = EscrowManager::get_escrow(e)?;
+        if platform_address != escrow.roles.platform_address {
+            return Err(ContractError::OnlyPlatformAddressExecuteThisFunction.into());
+        }
+
+        EscrowManager::extend_milestone_ttl(
+            e,
+            &escrow,
+            milestone_index,
+            ledgers_to_extend,
+            ledgers_to_extend,
+        )?;
+
+        MilestoneTtlExtended {
+            platform: platform_address,
+            milestone_index,
+            ledgers_to_extend,
+        }
+        .publish(e);
+
+        Ok(())
+    }
+
+    pub fn set_protocol_config(
+        e: Env,
+        admin: Address,
+        protocol_config: ProtocolConfig,
+    ) -> Result<(), Error> {
+        ProtocolManager::set_protocol_config(&e, &admin, &protocol_config)?;
+        ProtocolConfigUpdated {
+            admin,
+            protocol_config,
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    pub fn get_protocol_config(e: Env) -> ProtocolConfig {
+        ProtocolManager::get_protocol_config(&e)
+    }
+
+    pub fn upgrade(
+        e: Env,
+        platform_address: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        UpgradeManager::upgrade(&e, &platform_address, &new_wasm_hash)?;
+        EscrowUpgraded {
+            platform: platform_address,
+            new_wasm_hash,
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    /// Must be called after `upgrade`, once the new code is installed.
+    pub fn migrate(e: Env, platform_address: Address) -> Result<(), Error> {
+        let from_version = UpgradeManager::migrate(&e, &platform_address)?;
+        EscrowMigrated {
+            from_version,
+            to_version: CONTRACT_VERSION,
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    pub fn version(_e: Env) -> u32 {
+        CONTRACT_VERSION
+    }
+
     ////////////////////////
     // Milestones /////
     ////////////////////////
@@ -145,29 +370,208 @@ impl EscrowContract {
     pub fn change_milestone_status(
         e: Env,
         milestone_index: i128,
-        new_status: String,
-        new_evidence: Option<String>,
+        new_status: MilestoneStatus,
+        new_evidence: Option<EvidenceSubmission>,
         service_provider: Address,
-    ) -> Result<(), ContractError> {
-        let escrow = MilestoneManager::change_milestone_status(
+    ) -> Result<(), Error> {
+        let (escrow, old_status, evidence) = MilestoneManager::change_milestone_status(
             &e,
             milestone_index,
             new_status,
             new_evidence,
             service_provider,
         )?;
-        MilestoneStatusChanged { escrow }.publish(&e);
+        if let Some(evidence) = evidence {
+            EvidenceSubmitted {
+                engagement_id: escrow.engagement_id.clone(),
+                milestone_index,
+                evidence,
+            }
+            .publish(&e);
+        }
+        MilestoneStatusChanged {
+            engagement_id: escrow.engagement_id,
+            milestone_index,
+            old_status,
+            new_status,
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    /// Changes the status of several milestones in one call. Publishes the same events as
+    /// one `change_milestone_status` call per entry.
+    pub fn change_milestone_statuses(
+        e: Env,
+        changes: Vec<(u32, MilestoneStatus, Option<EvidenceSubmission>)>,
+        service_provider: Address,
+    ) -> Result<(), Error> {
+        let (escrow, applied_changes) =
+            MilestoneManager::change_milestone_statuses(&e, changes.clone(), service_provider)?;
+        for ((milestone_index, new_status, _), (old_status, evidence)) in
+            changes.iter().zip(applied_changes.iter())
+        {
+            if let Some(evidence) = evidence {
+                EvidenceSubmitted {
+                    engagement_id: escrow.engagement_id.clone(),
+                    milestone_index: milestone_index as i128,
+                    evidence,
+                }
+                .publish(&e);
+            }
+            MilestoneStatusChanged {
+                engagement_id: escrow.engagement_id.clone(),
+                milestone_index: milestone_index as i128,
+                old_status,
+                new_status,
+            }
+            .publish(&e);
+        }
         Ok(())
     }
 
+    pub fn get_milestone_evidence(
+        e: Env,
+        milestone_index: i128,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<Evidence>, Error> {
+        MilestoneManager::get_milestone_evidence(&e, milestone_index, None, start, limit)
+    }
+
+    /// Evidence log of an earlier revision of the milestone, kept after an update replaced it.
+    pub fn get_milestone_evidence_at(
+        e: Env,
+        milestone_index: i128,
+        revision: u32,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<Evidence>, Error> {
+        MilestoneManager::get_milestone_evidence(&e, milestone_index, Some(revision), start, limit)
+    }
+
+    pub fn get_milestone_revision(e: Env, milestone_index: i128) -> Result<u32, Error> {
+        MilestoneManager::get_milestone_revision(&e, milestone_index)
+    }
+
     pub fn approve_milestone(
         e: Env,
         milestone_index: i128,
         approver: Address,
-    ) -> Result<(), ContractError> {
-        let escrow =
-            MilestoneManager::change_milestone_approved_flag(&e, milestone_index, approver)?;
-        MilestoneApproved { escrow }.publish(&e);
+    ) -> Result<(), Error> {
+        let (escrow, approvals) = MilestoneManager::change_milestone_approved_flag(
+            &e,
+            milestone_index,
+            approver.clone(),
+        )?;
+        MilestoneApprovalAdded {
+            engagement_id: escrow.engagement_id.clone(),
+            approver,
+            milestone_index,
+            approvals,
+            approval_threshold: escrow.approval_threshold,
+        }
+        .publish(&e);
+        if approvals >= escrow.approval_threshold {
+            let index = validate_milestone_index(&escrow, milestone_index)?;
+            publish_milestone_approved(&e, &escrow, index)?;
+        }
+        Ok(())
+    }
+
+    /// Approves several milestones in one call. Publishes the same events as one
+    /// `approve_milestone` call per index.
+    pub fn approve_milestones(
+        e: Env,
+        milestone_indexes: Vec<u32>,
+        approver: Address,
+    ) -> Result<(), Error> {
+        let (escrow, approval_counts) =
+            MilestoneManager::approve_milestones(&e, milestone_indexes.clone(), approver.clone())?;
+        for (milestone_index, approvals) in milestone_indexes.iter().zip(approval_counts.iter()) {
+            MilestoneApprovalAdded {
+                engagement_id: escrow.engagement_id.clone(),
+                approver: approver.clone(),
+                milestone_index: milestone_index as i128,
+                approvals,
+                approval_threshold: escrow.approval_threshold,
+            }
+            .publish(&e);
+            if approvals >= escrow.approval_threshold {
+                publish_milestone_approved(&e, &escrow, milestone_index)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Approves a submitted milestone the approvers left unanswered for the escrow's
+    /// review period. Anyone may call it.
+    pub fn auto_approve(e: Env, milestone_index: i128) -> Result<(), Error> {
+        let (escrow, submitted_at) = MilestoneManager::auto_approve(&e, milestone_index)?;
+        let index = validate_milestone_index(&escrow, milestone_index)?;
+        let milestone = escrow.milestones.get(index).unwrap();
+        MilestoneAutoApproved {
+            engagement_id: escrow.engagement_id,
+            milestone_index,
+            amount: milestone.amount,
+            submitted_at,
+            approved_at: e.ledger().timestamp(),
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    pub fn withdraw_milestone_approval(
+        e: Env,
+        milestone_index: i128,
+        approver: Address,
+    ) -> Result<(), Error> {
+        let (escrow, approvals) =
+            MilestoneManager::withdraw_milestone_approval(&e, milestone_index, approver.clone())?;
+        MilestoneApprovalWithdrawn {
+            engagement_id: escrow.engagement_id,
+            approver,
+            milestone_index,
+            approvals,
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    pub fn get_milestone_approvals(e: Env, milestone_index: i128) -> Result<Vec<Address>, Error> {
+        MilestoneManager::get_milestone_approvals(&e, milestone_index)
+    }
+
+    pub fn reject_milestone(
+        e: Env,
+        milestone_index: i128,
+        approver: Address,
+        reason: String,
+    ) -> Result<(), Error> {
+        let escrow = MilestoneManager::reject_milestone(
+            &e,
+            milestone_index,
+            approver.clone(),
+            reason.clone(),
+        )?;
+        MilestoneRejected {
+            engagement_id: escrow.engagement_id,
+            approver,
+            milestone_index,
+            reason,
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    pub fn sweep_dust(e: Env, platform_address: Address) -> Result<(), Error> {
+        let (escrow, amount) = EscrowManager::sweep_dust(&e, &platform_address)?;
+        DustSwept {
+            engagement_id: escrow.engagement_id,
+            platform: platform_address,
+            amount,
+        }
+        .publish(&e);
         Ok(())
     }
 
@@ -178,22 +582,275 @@ impl EscrowContract {
     pub fn resolve_dispute(
         e: Env,
         dispute_resolver: Address,
-        trustless_work_address: Address,
         distributions: Map<Address, i128>,
-    ) -> Result<(), ContractError> {
-        let escrow = DisputeManager::resolve_dispute(
+    ) -> Result<(), Error> {
+        let (escrow, outcome) = DisputeManager::resolve_dispute(
             &e,
-            dispute_resolver,
-            trustless_work_address,
-            distributions,
+            dispute_resolver.clone(),
+            distributions.clone(),
         )?;
-        DisputeResolved { escrow }.publish(&e);
+        let target = DisputeTarget::Escrow;
+        publish_resolution(&e, &escrow, target, dispute_resolver, distributions, outcome)
+    }
+
+    /// Vote of an arbitration panel resolver on how to settle the escrow dispute. The
+    /// dispute is settled as soon as a quorum of the panel submits the same distributions.
+    pub fn vote_dispute_resolution(
+        e: Env,
+        resolver: Address,
+        distributions: Map<Address, i128>,
+    ) -> Result<(), Error> {
+        let (escrow, outcome) =
+            DisputeManager::vote_dispute_resolution(&e, resolver.clone(), distributions.clone())?;
+        DisputeVoteCast {
+            engagement_id: escrow.engagement_id.clone(),
+            resolver: resolver.clone(),
+            target: DisputeTarget::Escrow,
+            distributions: distributions.clone(),
+        }
+        .publish(&e);
+        let target = DisputeTarget::Escrow;
+        publish_resolution(&e, &escrow, target, resolver, distributions, outcome)
+    }
+
+    pub fn get_dispute_voting(e: Env, target: DisputeTarget) -> Result<DisputeVoting, Error> {
+        DisputeManager::get_dispute_voting(&e, target)
+    }
+
+    pub fn quote_resolution(
+        e: Env,
+        distributions: Map<Address, i128>,
+    ) -> Result<PayoutQuote, Error> {
+        DisputeManager::quote_resolution(&e, distributions)
+    }
+
+    pub fn dispute_escrow(e: Env, signer: Address, reason: String) -> Result<(), Error> {
+        let escrow = DisputeManager::dispute_escrow(&e, signer.clone(), reason.clone())?;
+        EscrowDisputed {
+            engagement_id: escrow.engagement_id,
+            signer,
+            reason,
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    /// Lets any escrow role put its side of an open dispute on record.
+    pub fn submit_dispute_statement(
+        e: Env,
+        signer: Address,
+        uri: String,
+        hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        let (escrow, statement) =
+            DisputeManager::submit_dispute_statement(&e, signer, uri, hash)?;
+        DisputeStatementSubmitted {
+            engagement_id: escrow.engagement_id,
+            statement,
+        }
+        .publish(&e);
         Ok(())
     }
 
-    pub fn dispute_escrow(e: Env, signer: Address) -> Result<(), ContractError> {
-        let escrow = DisputeManager::dispute_escrow(&e, signer)?;
-        EscrowDisputed { escrow }.publish(&e);
+    pub fn get_dispute_count(e: Env) -> u32 {
+        DisputeManager::get_dispute_count(&e)
+    }
+
+    pub fn get_dispute_record(e: Env, dispute_id: u32) -> Result<DisputeRecord, Error> {
+        DisputeManager::get_dispute_record(&e, dispute_id)
+    }
+
+    pub fn dispute_milestone(e: Env, milestone_index: i128, signer: Address) -> Result<(), Error> {
+        let escrow = DisputeManager::dispute_milestone(&e, milestone_index, signer.clone())?;
+        MilestoneDisputed {
+            engagement_id: escrow.engagement_id,
+            signer,
+            milestone_index,
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    pub fn resolve_milestone_dispute(
+        e: Env,
+        milestone_index: i128,
+        dispute_resolver: Address,
+        distributions: Map<Address, i128>,
+    ) -> Result<(), Error> {
+        let (escrow, outcome) = DisputeManager::resolve_milestone_dispute(
+            &e,
+            milestone_index,
+            dispute_resolver.clone(),
+            distributions.clone(),
+        )?;
+        let target = DisputeTarget::Milestone(validate_milestone_index(&escrow, milestone_index)?);
+        publish_resolution(&e, &escrow, target, dispute_resolver, distributions, outcome)
+    }
+
+    pub fn vote_milestone_resolution(
+        e: Env,
+        milestone_index: i128,
+        resolver: Address,
+        distributions: Map<Address, i128>,
+    ) -> Result<(), Error> {
+        let (escrow, outcome) = DisputeManager::vote_milestone_resolution(
+            &e,
+            milestone_index,
+            resolver.clone(),
+            distributions.clone(),
+        )?;
+        let target = DisputeTarget::Milestone(validate_milestone_index(&escrow, milestone_index)?);
+        DisputeVoteCast {
+            engagement_id: escrow.engagement_id.clone(),
+            resolver: resolver.clone(),
+            target,
+            distributions: distributions.clone(),
+        }
+        .publish(&e);
+        publish_resolution(&e, &escrow, target, resolver, distributions, outcome)
+    }
+
+    ////////////////////////
+    // Appeals /////
+    ////////////////////////
+
+    /// Appeals a resolution held in its appeal window. Only the approver or the service
+    /// provider may appeal, and only once.
+    pub fn appeal_resolution(e: Env, signer: Address, target: DisputeTarget) -> Result<(), Error> {
+        let escrow = AppealManager::appeal_resolution(&e, signer.clone(), target)?;
+        ResolutionAppealed {
+            engagement_id: escrow.engagement_id,
+            target,
+            appellant: signer,
+        }
+        .publish(&e);
         Ok(())
     }
+
+    pub fn resolve_appeal(
+        e: Env,
+        appeal_resolver: Address,
+        target: DisputeTarget,
+        distributions: Map<Address, i128>,
+    ) -> Result<(), Error> {
+        let (escrow, quote) = AppealManager::resolve_appeal(
+            &e,
+            appeal_resolver.clone(),
+            target,
+            distributions.clone(),
+        )?;
+        let outcome = ResolutionOutcome::Executed(quote);
+        publish_resolution(&e, &escrow, target, appeal_resolver, distributions, outcome)
+    }
+
+    /// Pays out a resolution once its appeal window has passed without an appeal.
+    pub fn finalize_resolution(e: Env, target: DisputeTarget) -> Result<(), Error> {
+        let (escrow, pending, quote) = AppealManager::finalize_resolution(&e, target)?;
+        let (decided_by, distributions) = (pending.decided_by, pending.distributions);
+        let outcome = ResolutionOutcome::Executed(quote);
+        publish_resolution(&e, &escrow, target, decided_by, distributions, outcome)
+    }
+
+    pub fn get_pending_resolution(
+        e: Env,
+        target: DisputeTarget,
+    ) -> Result<PendingResolution, Error> {
+        AppealManager::get_pending_resolution(&e, target)
+    }
+}
+
+fn publish_resolution(
+    e: &Env,
+    escrow: &Escrow,
+    target: DisputeTarget,
+    decided_by: Address,
+    distributions: Map<Address, i128>,
+    outcome: ResolutionOutcome,
+) -> Result<(), Error> {
+    let quote = match outcome {
+        ResolutionOutcome::Voting => return Ok(()),
+        ResolutionOutcome::Posted { appealable_until } => {
+            ResolutionPosted {
+                engagement_id: escrow.engagement_id.clone(),
+                target,
+                decided_by,
+                distributions,
+                appealable_until,
+            }
+            .publish(e);
+            return Ok(());
+        }
+        ResolutionOutcome::Executed(quote) => quote,
+    };
+
+    publish_payout(e, escrow, &quote)?;
+    match target {
+        DisputeTarget::Escrow => DisputeResolved {
+            engagement_id: escrow.engagement_id.clone(),
+            resolved_by: decided_by,
+            distributions,
+        }
+        .publish(e),
+        DisputeTarget::Milestone(index) => MilestoneDisputeResolved {
+            engagement_id: escrow.engagement_id.clone(),
+            dispute_resolver: decided_by,
+            milestone_index: index as i128,
+            distributions,
+        }
+        .publish(e),
+    }
+    Ok(())
+}
+
+fn publish_milestone_approved(e: &Env, escrow: &Escrow, milestone_index: u32) -> Result<(), Error> {
+    let milestone = escrow
+        .milestones
+        .get(milestone_index)
+        .ok_or(ContractError::InvalidMileStoneIndex)?;
+    MilestoneApproved {
+        engagement_id: escrow.engagement_id.clone(),
+        milestone_index: milestone_index as i128,
+        amount: milestone.amount,
+    }
+    .publish(e);
+    Ok(())
+}
+
+/// Publishes the breakdown of `quote`, then one event per transfer `PayoutManager::execute`
+/// made for it, skipping the empty ones it skips too.
+fn publish_payout(e: &Env, escrow: &Escrow, quote: &PayoutQuote) -> Result<(), Error> {
+    PayoutBreakdown {
+        engagement_id: escrow.engagement_id.clone(),
+        protocol_fee: quote.protocol_fee,
+        platform_fee: quote.platform_fee,
+        payouts: quote.payouts.clone(),
+    }
+    .publish(e);
+
+    let treasury = ProtocolManager::get_protocol_config(e).treasury;
+    let transfers = [
+        (TransferKind::ProtocolFee, treasury, quote.protocol_fee),
+        (
+            TransferKind::PlatformFee,
+            escrow.roles.platform_address.clone(),
+            quote.platform_fee,
+        ),
+    ];
+    let payouts = quote
+        .payouts
+        .iter()
+        .map(|payout| (TransferKind::Payout, payout.recipient, payout.amount));
+
+    for (kind, recipient, amount) in transfers.into_iter().chain(payouts) {
+        if amount > 0 {
+            FundsTransferred {
+                engagement_id: escrow.engagement_id.clone(),
+                kind,
+                recipient,
+                amount,
+            }
+            .publish(e);
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,131 @@
+use soroban_sdk::token::Client as TokenClient;
+use soroban_sdk::{Address, Env, Error, Map};
+
+use crate::core::dispute::DisputeManager;
+use crate::core::escrow::EscrowManager;
+use crate::error::DisputeError;
+use crate::storage::types::{
+    AppealWindow, DataKey, DisputeTarget, Escrow, PayoutQuote, PendingResolution,
+};
+
+use super::validators::dispute::{
+    validate_appeal_conditions, validate_appeal_resolution_conditions,
+    validate_resolution_finalization_conditions,
+};
+
+/// Holds dispute resolutions during the appeal window of escrows that have one.
+pub struct AppealManager;
+
+impl AppealManager {
+    /// Stores the decision instead of paying it out. The funds stay locked until it is
+    /// finalized or, if appealed, replaced by the appeal resolver's decision.
+    pub fn post_resolution(
+        e: &Env,
+        target: DisputeTarget,
+        window: &AppealWindow,
+        decided_by: Address,
+        distributions: Map<Address, i128>,
+        total: i128,
+    ) -> PendingResolution {
+        let pending = PendingResolution {
+            decided_by,
+            distributions,
+            total,
+            appealable_until: e.ledger().sequence().saturating_add(window.ledgers),
+            appealed: false,
+        };
+        e.storage()
+            .persistent()
+            .set(&DataKey::PendingResolution(target), &pending);
+        pending
+    }
+
+    pub fn get_pending(e: &Env, target: DisputeTarget) -> Option<PendingResolution> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::PendingResolution(target))
+    }
+
+    pub fn get_pending_resolution(
+        e: &Env,
+        target: DisputeTarget,
+    ) -> Result<PendingResolution, Error> {
+        Self::get_pending(e, target).ok_or(DisputeError::PendingResolutionNotFound.into())
+    }
+
+    pub fn appeal_resolution(
+        e: &Env,
+        signer: Address,
+        target: DisputeTarget,
+    ) -> Result<Escrow, Error> {
+        signer.require_auth();
+        let escrow = EscrowManager::get_escrow(e)?;
+        let mut pending = Self::get_pending_resolution(e, target)?;
+        validate_appeal_conditions(e, &escrow, &pending, &signer)?;
+
+        pending.appealed = true;
+        e.storage()
+            .persistent()
+            .set(&DataKey::PendingResolution(target), &pending);
+
+        Ok(escrow)
+    }
+
+    /// Settles an appealed resolution with the appeal resolver's own distributions, which
+    /// are paid out right away.
+    pub fn resolve_appeal(
+        e: &Env,
+        appeal_resolver: Address,
+        target: DisputeTarget,
+        distributions: Map<Address, i128>,
+    ) -> Result<(Escrow, PayoutQuote), Error> {
+        appeal_resolver.require_auth();
+        let escrow = EscrowManager::get_escrow(e)?;
+        let pending = Self::get_pending_resolution(e, target)?;
+        validate_appeal_resolution_conditions(&escrow, &pending, &appeal_resolver)?;
+
+        let total = DisputeManager::sum_distributions(&distributions)?;
+        DisputeManager::validate_resolution_terms(e, &escrow, target, total)?;
+
+        Self::remove_pending(e, target);
+        DisputeManager::execute_resolution(e, escrow, target, &distributions, total)
+    }
+
+    /// Pays out a resolution whose appeal window has passed without an appeal. Anyone may
+    /// call it, since the outcome is already fixed.
+    pub fn finalize_resolution(
+        e: &Env,
+        target: DisputeTarget,
+    ) -> Result<(Escrow, PendingResolution, PayoutQuote), Error> {
+        let escrow = EscrowManager::get_escrow(e)?;
+        let pending = Self::get_pending_resolution(e, target)?;
+
+        let token_client = TokenClient::new(e, &escrow.trustline.address);
+        let current_balance = token_client.balance(&e.current_contract_address());
+        validate_resolution_finalization_conditions(e, &pending, current_balance)?;
+
+        Self::remove_pending(e, target);
+        let (escrow, quote) = DisputeManager::execute_resolution(
+            e,
+            escrow,
+            target,
+            &pending.distributions,
+            pending.total,
+        )?;
+        Ok((escrow, pending, quote))
+    }
+
+    fn remove_pending(e: &Env, target: DisputeTarget) {
+        e.storage()
+            .persistent()
+            .remove(&DataKey::PendingResolution(target));
+    }
+
+    pub fn extend_ttl(e: &Env, target: DisputeTarget, threshold: u32, extend_to: u32) {
+        let storage = e.storage().persistent();
+        let key = DataKey::PendingResolution(target);
+        if storage.has(&key) {
+            storage.extend_ttl(&key, threshold, extend_to);
+        }
+    }
+}
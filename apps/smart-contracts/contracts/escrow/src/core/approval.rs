@@ -0,0 +1,74 @@
+use soroban_sdk::{Address, Env, Error, Vec};
+
+use crate::core::validators::milestone::is_approver;
+use crate::error::WorkflowError;
+use crate::storage::types::{DataKey, Escrow};
+
+/// Keeps, per milestone, the approvers that have approved it so far.
+pub struct ApprovalManager;
+
+impl ApprovalManager {
+    pub fn get_approvals(e: &Env, milestone_index: u32) -> Vec<Address> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::MilestoneApprovals(milestone_index))
+            .unwrap_or(Vec::new(e))
+    }
+
+    /// Approvals given by addresses that are still approvers of `escrow`. An approver that
+    /// was replaced since approving no longer counts towards the threshold.
+    pub fn count_approvals(escrow: &Escrow, approvals: &Vec<Address>) -> u32 {
+        approvals
+            .iter()
+            .filter(|approver| is_approver(escrow, approver))
+            .count() as u32
+    }
+
+    pub fn add_approval(
+        e: &Env,
+        milestone_index: u32,
+        approver: &Address,
+    ) -> Result<Vec<Address>, Error> {
+        let mut approvals = Self::get_approvals(e, milestone_index);
+        if approvals.contains(approver) {
+            return Err(WorkflowError::MilestoneAlreadyApprovedBySigner.into());
+        }
+
+        approvals.push_back(approver.clone());
+        e.storage()
+            .persistent()
+            .set(&DataKey::MilestoneApprovals(milestone_index), &approvals);
+        Ok(approvals)
+    }
+
+    pub fn remove_approval(
+        e: &Env,
+        milestone_index: u32,
+        approver: &Address,
+    ) -> Result<Vec<Address>, Error> {
+        let mut approvals = Self::get_approvals(e, milestone_index);
+        let position = approvals
+            .first_index_of(approver)
+            .ok_or(WorkflowError::MilestoneApprovalNotFound)?;
+
+        approvals.remove(position);
+        e.storage()
+            .persistent()
+            .set(&DataKey::MilestoneApprovals(milestone_index), &approvals);
+        Ok(approvals)
+    }
+
+    pub fn clear_approvals(e: &Env, milestone_index: u32) {
+        e.storage()
+            .persistent()
+            .remove(&DataKey::MilestoneApprovals(milestone_index));
+    }
+
+    pub fn extend_ttl(e: &Env, milestone_index: u32, threshold: u32, extend_to: u32) {
+        let storage = e.storage().persistent();
+        let key = DataKey::MilestoneApprovals(milestone_index);
+        if storage.has(&key) {
+            storage.extend_ttl(&key, threshold, extend_to);
+        }
+    }
+}
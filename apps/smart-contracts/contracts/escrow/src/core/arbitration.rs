@@ -0,0 +1,77 @@
+use soroban_sdk::{Address, Env, Map, Vec};
+
+use crate::storage::types::{
+    Arbitration, DataKey, DisputeTarget, DisputeVote, DisputeVoting, Escrow,
+};
+
+/// Keeps the votes of the arbitration panel on each open dispute.
+pub struct ArbitrationManager;
+
+impl ArbitrationManager {
+    /// Opens the panel vote on a dispute that was just raised. Escrows without a panel are
+    /// settled by the dispute resolver alone, so nothing is stored for them.
+    pub fn open_voting(e: &Env, escrow: &Escrow, target: DisputeTarget) {
+        if let Arbitration::Panel(panel) = &escrow.arbitration {
+            let voting = DisputeVoting {
+                deadline: e.ledger().timestamp().saturating_add(panel.voting_period),
+                votes: Vec::new(e),
+            };
+            e.storage()
+                .persistent()
+                .set(&DataKey::DisputeVoting(target), &voting);
+        }
+    }
+
+    pub fn get_voting(e: &Env, target: DisputeTarget) -> Option<DisputeVoting> {
+        e.storage().persistent().get(&DataKey::DisputeVoting(target))
+    }
+
+    /// Records the resolver's vote, replacing any vote it cast before, and tells whether
+    /// `distributions` now has the support of a quorum of the panel.
+    pub fn cast_vote(
+        e: &Env,
+        escrow: &Escrow,
+        target: DisputeTarget,
+        mut voting: DisputeVoting,
+        resolver: &Address,
+        distributions: &Map<Address, i128>,
+    ) -> bool {
+        let quorum = match &escrow.arbitration {
+            Arbitration::None => return false,
+            Arbitration::Panel(panel) => panel.quorum,
+        };
+
+        if let Some(position) = voting.votes.iter().position(|vote| &vote.resolver == resolver) {
+            voting.votes.remove(position as u32);
+        }
+        voting.votes.push_back(DisputeVote {
+            resolver: resolver.clone(),
+            distributions: distributions.clone(),
+        });
+
+        let supporting_votes = voting
+            .votes
+            .iter()
+            .filter(|vote| &vote.distributions == distributions)
+            .count() as u32;
+
+        e.storage()
+            .persistent()
+            .set(&DataKey::DisputeVoting(target), &voting);
+        supporting_votes >= quorum
+    }
+
+    pub fn close_voting(e: &Env, target: DisputeTarget) {
+        e.storage()
+            .persistent()
+            .remove(&DataKey::DisputeVoting(target));
+    }
+
+    pub fn extend_ttl(e: &Env, target: DisputeTarget, threshold: u32, extend_to: u32) {
+        let storage = e.storage().persistent();
+        let key = DataKey::DisputeVoting(target);
+        if storage.has(&key) {
+            storage.extend_ttl(&key, threshold, extend_to);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use soroban_sdk::{Address, Env, Error, Vec};
+
+use crate::modules::math::{BasicArithmetic, BasicMath, SafeArithmetic, SafeMath};
+use crate::storage::types::{DataKey, Deposit};
+
+pub struct DepositManager;
+
+impl DepositManager {
+    pub fn record_deposit(e: &Env, depositor: &Address, amount: i128) -> Result<i128, Error> {
+        let storage = e.storage().persistent();
+        let key = DataKey::Deposit(depositor.clone());
+        let previous: i128 = storage.get(&key).unwrap_or(0);
+
+        if previous == 0 {
+            let mut depositors = Self::get_depositors(e);
+            depositors.push_back(depositor.clone());
+            storage.set(&DataKey::Depositors, &depositors);
+        }
+
+        let total = BasicMath::safe_add(previous, amount)?;
+        storage.set(&key, &total);
+        Ok(total)
+    }
+
+    pub fn get_deposit(e: &Env, depositor: &Address) -> i128 {
+        e.storage()
+            .persistent()
+            .get(&DataKey::Deposit(depositor.clone()))
+            .unwrap_or(0)
+    }
+
+    /// Removes every recorded deposit once the balance has been returned to the depositors.
+    pub fn clear_deposits(e: &Env) {
+        let storage = e.storage().persistent();
+        for depositor in Self::get_depositors(e).iter() {
+            storage.remove(&DataKey::Deposit(depositor));
+        }
+        storage.remove(&DataKey::Depositors);
+    }
+
+    pub fn get_deposits(e: &Env) -> Vec<Deposit> {
+        let mut deposits: Vec<Deposit> = Vec::new(e);
+        for depositor in Self::get_depositors(e).iter() {
+            let amount = Self::get_deposit(e, &depositor);
+            deposits.push_back(Deposit { depositor, amount });
+        }
+        deposits
+    }
+
+    /// Splits `amount` between the depositors in proportion to what each one contributed.
+    /// Integer division rounds every share down, so the last depositor receives the
+    /// remainder and the shares always add up to `amount`.
+    pub fn calculate_refund_shares(e: &Env, amount: i128) -> Result<Vec<Deposit>, Error> {
+        let deposits = Self::get_deposits(e);
+
+        let mut total_deposited: i128 = 0;
+        for deposit in deposits.iter() {
+            total_deposited = BasicMath::safe_add(total_deposited, deposit.amount)?;
+        }
+
+        let mut shares: Vec<Deposit> = Vec::new(e);
+        if total_deposited <= 0 {
+            return Ok(shares);
+        }
+
+        let mut remaining = amount;
+        let last_index = deposits.len() - 1;
+        for (index, deposit) in deposits.iter().enumerate() {
+            let share = if index as u32 == last_index {
+                remaining
+            } else {
+                SafeMath::safe_share(amount, deposit.amount, total_deposited)?
+            };
+            remaining = BasicMath::safe_sub(remaining, share)?;
+            shares.push_back(Deposit {
+                depositor: deposit.depositor,
+                amount: share,
+            });
+        }
+
+        Ok(shares)
+    }
+
+    pub fn extend_ttl(e: &Env, threshold: u32, extend_to: u32) {
+        let storage = e.storage().persistent();
+        if !storage.has(&DataKey::Depositors) {
+            return;
+        }
+
+        storage.extend_ttl(&DataKey::Depositors, threshold, extend_to);
+        for depositor in Self::get_depositors(e).iter() {
+            storage.extend_ttl(&DataKey::Deposit(depositor), threshold, extend_to);
+        }
+    }
+
+    fn get_depositors(e: &Env) -> Vec<Address> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::Depositors)
+            .unwrap_or(Vec::new(e))
+    }
+}
@@ -1,17 +1,36 @@
 use soroban_sdk::token::Client as TokenClient;
-use soroban_sdk::{Address, Env, Map};
+use soroban_sdk::{Address, BytesN, Env, Error, Map, String, Vec};
 
+use crate::core::appeal::AppealManager;
+use crate::core::arbitration::ArbitrationManager;
 use crate::core::escrow::EscrowManager;
-use crate::error::ContractError;
-use crate::modules::{
-    fee::{FeeCalculator, FeeCalculatorTrait},
-    math::{BasicArithmetic, BasicMath},
+use crate::core::payout::PayoutManager;
+use crate::error::{ContractError, DisputeError};
+use crate::modules::math::{BasicArithmetic, BasicMath};
+use crate::storage::escrow::EscrowStorage;
+use crate::storage::types::{
+    Appeal, DataKey, DisputeRecord, DisputeTarget, DisputeVoting, Escrow, Evidence,
+    PayoutQuote,
 };
-use crate::storage::types::{DataKey, Escrow};
 
 use super::validators::dispute::{
     validate_dispute_flag_change_conditions, validate_dispute_resolution_conditions,
+    validate_dispute_statement_conditions, validate_dispute_vote_conditions,
+    validate_escrow_resolution_terms, validate_milestone_dispute_conditions,
+    validate_milestone_dispute_resolution_conditions, validate_milestone_resolution_terms,
+    validate_no_pending_resolution,
 };
+use super::validators::milestone::validate_milestone_index;
+
+/// What a dispute decision led to.
+pub enum ResolutionOutcome {
+    /// The panel vote was recorded but has not reached a quorum yet.
+    Voting,
+    /// The decision is held until its appeal window passes.
+    Posted { appealable_until: u32 },
+    /// The decision has been paid out as quoted.
+    Executed(PayoutQuote),
+}
 
 pub struct DisputeManager;
 
@@ -19,76 +38,333 @@ impl DisputeManager {
     pub fn resolve_dispute(
         e: &Env,
         dispute_resolver: Address,
-        trustless_work_address: Address,
         distributions: Map<Address, i128>,
-    ) -> Result<Escrow, ContractError> {
+    ) -> Result<(Escrow, ResolutionOutcome), Error> {
         dispute_resolver.require_auth();
-        let mut escrow = EscrowManager::get_escrow(e)?;
+        let escrow = EscrowManager::get_escrow(e)?;
         let contract_address = e.current_contract_address();
+        let target = DisputeTarget::Escrow;
+        validate_no_pending_resolution(&AppealManager::get_pending(e, target))?;
 
-        let token_client = TokenClient::new(&e, &escrow.trustline.address);
+        let token_client = TokenClient::new(e, &escrow.trustline.address);
         let current_balance = token_client.balance(&contract_address);
 
-        let mut total: i128 = 0;
-        for (_addr, amount) in distributions.iter() {
-            if amount <= 0 {
-                return Err(ContractError::AmountsToBeTransferredShouldBePositive);
-            }
-            total = BasicMath::safe_add(total, amount)?;
-        }
+        let total = Self::sum_distributions(&distributions)?;
 
         validate_dispute_resolution_conditions(
+            e,
             &escrow,
             &dispute_resolver,
+            &ArbitrationManager::get_voting(e, target),
             current_balance,
             total,
         )?;
 
-        let fee_result = FeeCalculator::calculate_standard_fees(total, escrow.platform_fee)?;
-        let total_fees =
-            BasicMath::safe_add(fee_result.trustless_work_fee, fee_result.platform_fee)?;
+        Self::settle(e, escrow, target, dispute_resolver, distributions, total)
+    }
 
-        if fee_result.trustless_work_fee > 0 {
-            token_client.transfer(
-                &contract_address,
-                &trustless_work_address,
-                &fee_result.trustless_work_fee,
-            );
+    /// Casts a panel resolver's vote on the escrow dispute. The dispute is settled as soon
+    /// as the vote completes a quorum.
+    pub fn vote_dispute_resolution(
+        e: &Env,
+        resolver: Address,
+        distributions: Map<Address, i128>,
+    ) -> Result<(Escrow, ResolutionOutcome), Error> {
+        Self::cast_vote(e, DisputeTarget::Escrow, resolver, distributions)
+    }
+
+    fn cast_vote(
+        e: &Env,
+        target: DisputeTarget,
+        resolver: Address,
+        distributions: Map<Address, i128>,
+    ) -> Result<(Escrow, ResolutionOutcome), Error> {
+        resolver.require_auth();
+        let escrow = EscrowManager::get_escrow(e)?;
+        let voting =
+            ArbitrationManager::get_voting(e, target).ok_or(DisputeError::DisputeVotingNotFound)?;
+        validate_dispute_vote_conditions(e, &escrow, &resolver, &voting)?;
+
+        let total = Self::sum_distributions(&distributions)?;
+        Self::validate_resolution_terms(e, &escrow, target, total)?;
+
+        if !ArbitrationManager::cast_vote(e, &escrow, target, voting, &resolver, &distributions) {
+            return Ok((escrow, ResolutionOutcome::Voting));
         }
-        if fee_result.platform_fee > 0 {
-            token_client.transfer(
-                &contract_address,
-                &escrow.roles.platform_address,
-                &fee_result.platform_fee,
-            );
+
+        Self::settle(e, escrow, target, resolver, distributions, total)
+    }
+
+    /// Checks `total` against what the disputed target can pay out.
+    pub fn validate_resolution_terms(
+        e: &Env,
+        escrow: &Escrow,
+        target: DisputeTarget,
+        total: i128,
+    ) -> Result<(), Error> {
+        let token_client = TokenClient::new(e, &escrow.trustline.address);
+        let current_balance = token_client.balance(&e.current_contract_address());
+
+        match target {
+            DisputeTarget::Escrow => {
+                validate_escrow_resolution_terms(escrow, current_balance, total)
+            }
+            DisputeTarget::Milestone(index) => {
+                let milestone = escrow
+                    .milestones
+                    .get(index)
+                    .ok_or(ContractError::InvalidMileStoneIndex)?;
+                validate_milestone_resolution_terms(escrow, &milestone, current_balance, total)
+            }
         }
+    }
 
-        for (addr, amount) in distributions.iter() {
-            if amount <= 0 {
-                continue;
+    /// Ends the vote on the dispute and either pays the decision out or, when the escrow
+    /// has an appeal window, holds it until the window passes.
+    fn settle(
+        e: &Env,
+        escrow: Escrow,
+        target: DisputeTarget,
+        decided_by: Address,
+        distributions: Map<Address, i128>,
+        total: i128,
+    ) -> Result<(Escrow, ResolutionOutcome), Error> {
+        ArbitrationManager::close_voting(e, target);
+
+        match &escrow.appeal {
+            Appeal::None => {
+                let (escrow, quote) =
+                    Self::execute_resolution(e, escrow, target, &distributions, total)?;
+                Ok((escrow, ResolutionOutcome::Executed(quote)))
             }
-            let fee_share = (amount * (total_fees as i128)) / total;
-            let net_amount = amount - fee_share;
-            if net_amount > 0 {
-                token_client.transfer(&contract_address, &addr, &net_amount);
+            Appeal::Window(window) => {
+                let pending = AppealManager::post_resolution(
+                    e,
+                    target,
+                    window,
+                    decided_by,
+                    distributions,
+                    total,
+                );
+                let appealable_until = pending.appealable_until;
+                Ok((escrow, ResolutionOutcome::Posted { appealable_until }))
             }
         }
+    }
 
-        escrow.flags.resolved = true;
-        escrow.flags.disputed = false;
-        e.storage().instance().set(&DataKey::Escrow, &escrow);
+    /// Pays out `distributions` and marks the escrow or the milestone as resolved. A
+    /// milestone resolution releases the escrow once no milestone is left unsettled.
+    pub fn execute_resolution(
+        e: &Env,
+        mut escrow: Escrow,
+        target: DisputeTarget,
+        distributions: &Map<Address, i128>,
+        total: i128,
+    ) -> Result<(Escrow, PayoutQuote), Error> {
+        let quote = PayoutManager::quote_distribution(e, &escrow, distributions, total)?;
+        PayoutManager::execute(e, &escrow, &quote)?;
 
-        Ok(escrow)
+        match target {
+            DisputeTarget::Escrow => {
+                escrow.flags.resolved = true;
+                escrow.flags.disputed = false;
+                EscrowStorage::save_header(e, &escrow);
+            }
+            DisputeTarget::Milestone(index) => {
+                let mut milestone = escrow
+                    .milestones
+                    .get(index)
+                    .ok_or(ContractError::InvalidMileStoneIndex)?;
+                milestone.disputed = false;
+                milestone.resolved = true;
+                escrow.milestones.set(index, milestone);
+                EscrowStorage::save_milestone(e, &escrow, index)?;
+                if escrow.milestones.iter().all(|m| m.released || m.resolved) {
+                    escrow.flags.released = true;
+                    EscrowStorage::save_header(e, &escrow);
+                }
+            }
+        }
+
+        Ok((escrow, quote))
     }
 
-    pub fn dispute_escrow(e: &Env, signer: Address) -> Result<Escrow, ContractError> {
+    pub fn dispute_escrow(e: &Env, signer: Address, reason: String) -> Result<Escrow, Error> {
         signer.require_auth();
         let mut escrow = EscrowManager::get_escrow(e)?;
-        validate_dispute_flag_change_conditions(&escrow, &signer)?;
+        validate_dispute_flag_change_conditions(&escrow, &signer, &reason)?;
 
         escrow.flags.disputed = true;
-        e.storage().instance().set(&DataKey::Escrow, &escrow);
+        EscrowStorage::save_header(e, &escrow);
+        ArbitrationManager::open_voting(e, &escrow, DisputeTarget::Escrow);
+
+        let record = DisputeRecord {
+            opened_by: signer,
+            opened_at: e.ledger().timestamp(),
+            reason,
+            statements: Vec::new(e),
+        };
+        let count = Self::get_dispute_count(e);
+        let storage = e.storage().persistent();
+        storage.set(&DataKey::Dispute(count), &record);
+        storage.set(&DataKey::DisputeCount, &(count + 1));
+
+        Ok(escrow)
+    }
+
+    /// Adds a statement to the record of the open dispute, the latest one. Statements are
+    /// kept after the dispute is resolved, so the resolution can be checked against them.
+    pub fn submit_dispute_statement(
+        e: &Env,
+        signer: Address,
+        uri: String,
+        hash: BytesN<32>,
+    ) -> Result<(Escrow, Evidence), Error> {
+        signer.require_auth();
+        let escrow = EscrowManager::get_escrow(e)?;
+        validate_dispute_statement_conditions(&escrow, &signer)?;
+
+        let dispute_id = Self::get_dispute_count(e)
+            .checked_sub(1)
+            .ok_or(DisputeError::DisputeRecordNotFound)?;
+        let mut record = Self::get_dispute_record(e, dispute_id)?;
+        let statement = Evidence {
+            submitter: signer,
+            timestamp: e.ledger().timestamp(),
+            uri,
+            hash,
+        };
+        record.statements.push_back(statement.clone());
+        e.storage()
+            .persistent()
+            .set(&DataKey::Dispute(dispute_id), &record);
+
+        Ok((escrow, statement))
+    }
+
+    /// Number of disputes opened on the escrow. Records are numbered from 0 in the order
+    /// the disputes were opened.
+    pub fn get_dispute_count(e: &Env) -> u32 {
+        e.storage()
+            .persistent()
+            .get(&DataKey::DisputeCount)
+            .unwrap_or(0)
+    }
+
+    pub fn get_dispute_record(e: &Env, dispute_id: u32) -> Result<DisputeRecord, Error> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::Dispute(dispute_id))
+            .ok_or(DisputeError::DisputeRecordNotFound.into())
+    }
+
+    pub fn extend_ttl(e: &Env, threshold: u32, extend_to: u32) {
+        let storage = e.storage().persistent();
+        let count = Self::get_dispute_count(e);
+        if count == 0 {
+            return;
+        }
+
+        storage.extend_ttl(&DataKey::DisputeCount, threshold, extend_to);
+        for dispute_id in 0..count {
+            storage.extend_ttl(&DataKey::Dispute(dispute_id), threshold, extend_to);
+        }
+    }
+
+    pub fn dispute_milestone(
+        e: &Env,
+        milestone_index: i128,
+        signer: Address,
+    ) -> Result<Escrow, Error> {
+        signer.require_auth();
+        let mut escrow = EscrowManager::get_escrow(e)?;
+        let index = validate_milestone_index(&escrow, milestone_index)?;
+
+        let mut milestone = escrow.milestones.get(index).unwrap();
+
+        validate_milestone_dispute_conditions(&escrow, &milestone, &signer)?;
+
+        milestone.disputed = true;
+        escrow.milestones.set(index, milestone);
+        EscrowStorage::save_milestone(e, &escrow, index)?;
+        ArbitrationManager::open_voting(e, &escrow, DisputeTarget::Milestone(index));
 
         Ok(escrow)
     }
+
+    /// Splits only the disputed milestone's amount between the given recipients. The rest
+    /// of the balance stays locked for the remaining milestones.
+    pub fn resolve_milestone_dispute(
+        e: &Env,
+        milestone_index: i128,
+        dispute_resolver: Address,
+        distributions: Map<Address, i128>,
+    ) -> Result<(Escrow, ResolutionOutcome), Error> {
+        dispute_resolver.require_auth();
+        let escrow = EscrowManager::get_escrow(e)?;
+        let index = validate_milestone_index(&escrow, milestone_index)?;
+
+        let milestone = escrow.milestones.get(index).unwrap();
+
+        let target = DisputeTarget::Milestone(index);
+        validate_no_pending_resolution(&AppealManager::get_pending(e, target))?;
+
+        let token_client = TokenClient::new(e, &escrow.trustline.address);
+        let current_balance = token_client.balance(&e.current_contract_address());
+
+        let total = Self::sum_distributions(&distributions)?;
+
+        validate_milestone_dispute_resolution_conditions(
+            e,
+            &escrow,
+            &milestone,
+            &dispute_resolver,
+            &ArbitrationManager::get_voting(e, target),
+            current_balance,
+            total,
+        )?;
+
+        Self::settle(e, escrow, target, dispute_resolver, distributions, total)
+    }
+
+    /// Casts a panel resolver's vote on a milestone dispute. The dispute is settled as soon
+    /// as the vote completes a quorum.
+    pub fn vote_milestone_resolution(
+        e: &Env,
+        milestone_index: i128,
+        resolver: Address,
+        distributions: Map<Address, i128>,
+    ) -> Result<(Escrow, ResolutionOutcome), Error> {
+        let escrow = EscrowManager::get_escrow(e)?;
+        let index = validate_milestone_index(&escrow, milestone_index)?;
+        Self::cast_vote(e, DisputeTarget::Milestone(index), resolver, distributions)
+    }
+
+    pub fn get_dispute_voting(e: &Env, target: DisputeTarget) -> Result<DisputeVoting, Error> {
+        ArbitrationManager::get_voting(e, target).ok_or(DisputeError::DisputeVotingNotFound.into())
+    }
+
+    pub fn quote_resolution(
+        e: &Env,
+        distributions: Map<Address, i128>,
+    ) -> Result<PayoutQuote, Error> {
+        let escrow = EscrowManager::get_escrow(e)?;
+        let total = Self::sum_distributions(&distributions)?;
+        if total <= 0 {
+            return Err(ContractError::TotalAmountCannotBeZero.into());
+        }
+
+        PayoutManager::quote_distribution(e, &escrow, &distributions, total)
+    }
+
+    pub fn sum_distributions(distributions: &Map<Address, i128>) -> Result<i128, Error> {
+        let mut total: i128 = 0;
+        for (_addr, amount) in distributions.iter() {
+            if amount <= 0 {
+                return Err(ContractError::AmountsToBeTransferredShouldBePositive.into());
+            }
+            total = BasicMath::safe_add(total, amount)?;
+        }
+        Ok(total)
+    }
 }
@@ -1,13 +1,31 @@
 use soroban_sdk::token::Client as TokenClient;
-use soroban_sdk::{Address, Env, Symbol, Vec};
+use soroban_sdk::{Address, Env, Error, Map, Symbol, Vec};
 
+use crate::core::appeal::AppealManager;
+use crate::core::approval::ApprovalManager;
+use crate::core::arbitration::ArbitrationManager;
+use crate::core::deposit::DepositManager;
+use crate::core::dispute::DisputeManager;
+use crate::core::evidence::EvidenceManager;
+use crate::core::payout::PayoutManager;
+use crate::core::proposal::ProposalManager;
+use crate::core::protocol::ProtocolManager;
+use crate::core::review::ReviewManager;
+use crate::core::upgrade::UpgradeManager;
 use crate::core::validators::escrow::{
+    validate_cancellation_conditions, validate_direct_update_conditions,
     validate_escrow_property_change_conditions, validate_fund_escrow_conditions,
-    validate_initialize_escrow_conditions, validate_release_conditions,
+    validate_initialize_escrow_conditions, validate_milestone_release_conditions,
+    validate_refund_conditions, validate_release_conditions, validate_sweep_conditions,
 };
+use crate::core::validators::milestone::validate_milestone_index;
 use crate::error::ContractError;
-use crate::modules::fee::{FeeCalculator, FeeCalculatorTrait};
-use crate::storage::types::{AddressBalance, DataKey, Escrow};
+use crate::modules::math::{BasicArithmetic, BasicMath};
+use crate::storage::escrow::EscrowStorage;
+use crate::storage::types::{
+    AddressBalance, Deposit, DisputeTarget, Escrow, EscrowChanges, EscrowPropertyChange,
+    EscrowUpdateProposal, MilestoneStatus, PayoutQuote,
+};
 
 pub struct EscrowManager;
 
@@ -17,11 +35,14 @@ impl EscrowManager {
         escrow.roles.receiver.clone()
     }
 
-    pub fn initialize_escrow(e: &Env, escrow_properties: Escrow) -> Result<Escrow, ContractError> {
-        validate_initialize_escrow_conditions(e, escrow_properties.clone())?;
-        e.storage()
-            .instance()
-            .set(&DataKey::Escrow, &escrow_properties);
+    pub fn initialize_escrow(e: &Env, escrow_properties: Escrow) -> Result<Escrow, Error> {
+        validate_initialize_escrow_conditions(
+            e,
+            escrow_properties.clone(),
+            ProtocolManager::get_protocol_fee_bps(e),
+        )?;
+        EscrowStorage::save(e, &escrow_properties);
+        UpgradeManager::set_stored_version(e);
         Ok(escrow_properties)
     }
 
@@ -30,85 +51,358 @@ impl EscrowManager {
         signer: &Address,
         expected_escrow: &Escrow,
         amount: i128,
-    ) -> Result<(), ContractError> {
+    ) -> Result<Escrow, Error> {
         let stored_escrow: Escrow = Self::get_escrow(e)?;
-        validate_fund_escrow_conditions(amount, &stored_escrow, expected_escrow)?;
+        let token_client = TokenClient::new(e, &stored_escrow.trustline.address);
+        let current_balance = token_client.balance(&e.current_contract_address());
+        let remaining_amount =
+            BasicMath::safe_sub(Self::get_unreleased_amount(&stored_escrow)?, current_balance)?;
+        validate_fund_escrow_conditions(
+            e,
+            amount,
+            &stored_escrow,
+            expected_escrow,
+            remaining_amount,
+        )?;
 
         signer.require_auth();
-        let token_client = TokenClient::new(e, &stored_escrow.trustline.address);
-        token_client.transfer(signer, &e.current_contract_address(), &amount);
-        Ok(())
+        token_client.transfer(signer, e.current_contract_address(), &amount);
+
+        DepositManager::record_deposit(e, signer, amount)?;
+        Ok(stored_escrow)
     }
 
     pub fn release_funds(
         e: &Env,
         release_signer: &Address,
-        trustless_work_address: &Address,
-    ) -> Result<(), ContractError> {
+    ) -> Result<(Escrow, PayoutQuote), Error> {
         release_signer.require_auth();
 
         let mut escrow = Self::get_escrow(e)?;
         validate_release_conditions(&escrow, release_signer)?;
 
+        let release_amount = Self::get_unreleased_amount(&escrow)?;
+        for index in 0..escrow.milestones.len() {
+            let mut milestone = escrow.milestones.get(index).unwrap();
+            if milestone.released || milestone.resolved {
+                continue;
+            }
+            milestone.released = true;
+            milestone.status = MilestoneStatus::Released;
+            escrow.milestones.set(index, milestone);
+        }
+
         escrow.flags.released = true;
-        e.storage().instance().set(&DataKey::Escrow, &escrow);
+        EscrowStorage::save(e, &escrow);
+
+        let quote = Self::pay_release_amount(e, &escrow, release_amount)?;
+        Ok((escrow, quote))
+    }
+
+    pub fn release_milestone_funds(
+        e: &Env,
+        milestone_index: i128,
+        release_signer: &Address,
+    ) -> Result<(Escrow, PayoutQuote), Error> {
+        release_signer.require_auth();
 
+        let mut escrow = Self::get_escrow(e)?;
+        let index = validate_milestone_index(&escrow, milestone_index)?;
+        let mut milestone = escrow.milestones.get(index).unwrap();
+
+        validate_milestone_release_conditions(&escrow, &milestone, release_signer)?;
+
+        milestone.released = true;
+        milestone.status = MilestoneStatus::Released;
+        escrow.milestones.set(index, milestone.clone());
+        EscrowStorage::save_milestone(e, &escrow, index)?;
+        if escrow.milestones.iter().all(|m| m.released || m.resolved) {
+            escrow.flags.released = true;
+            EscrowStorage::save_header(e, &escrow);
+        }
+
+        let quote = Self::pay_release_amount(e, &escrow, milestone.amount)?;
+
+        Ok((escrow, quote))
+    }
+
+    fn pay_release_amount(e: &Env, escrow: &Escrow, amount: i128) -> Result<PayoutQuote, Error> {
+        let token_client = TokenClient::new(e, &escrow.trustline.address);
+        if token_client.balance(&e.current_contract_address()) < amount {
+            return Err(ContractError::EscrowBalanceNotEnoughToSendEarnings.into());
+        }
+
+        let quote = PayoutManager::quote_release(e, escrow, amount)?;
+        PayoutManager::execute(e, escrow, &quote)?;
+        Ok(quote)
+    }
+
+    /// Sum of the milestones a call to `release_funds` would pay out.
+    fn get_unreleased_amount(escrow: &Escrow) -> Result<i128, Error> {
+        let mut amount: i128 = 0;
+        for milestone in escrow.milestones.iter() {
+            if !milestone.released && !milestone.resolved {
+                amount = BasicMath::safe_add(amount, milestone.amount)?;
+            }
+        }
+        Ok(amount)
+    }
+
+    pub fn quote_release(e: &Env) -> Result<PayoutQuote, Error> {
+        let escrow = Self::get_escrow(e)?;
+        let amount = Self::get_unreleased_amount(&escrow)?;
+        PayoutManager::quote_release(e, &escrow, amount)
+    }
+
+    /// Returns the contract balance to the depositors, pro rata to their recorded deposits,
+    /// once the deadline has passed without any approved milestone or open dispute.
+    pub fn refund(e: &Env) -> Result<(Escrow, Vec<Deposit>), Error> {
+        let escrow = Self::get_escrow(e)?;
         let contract_address = e.current_contract_address();
         let token_client = TokenClient::new(e, &escrow.trustline.address);
+        let current_balance = token_client.balance(&contract_address);
+
+        validate_refund_conditions(e, &escrow, current_balance)?;
+
+        let refunds = Self::return_balance_to_depositors(e, &escrow, current_balance)?;
+        Ok((escrow, refunds))
+    }
 
-        if token_client.balance(&contract_address) < escrow.amount {
-            return Err(ContractError::EscrowBalanceNotEnoughToSendEarnings);
+    /// Closes an escrow that has not paid anything out yet. Both the platform and the
+    /// approver must agree, and whatever balance it holds goes back to the depositors.
+    pub fn cancel_escrow(
+        e: &Env,
+        platform_address: &Address,
+        approver: &Address,
+    ) -> Result<(Escrow, Vec<Deposit>), Error> {
+        platform_address.require_auth();
+        if approver != platform_address {
+            approver.require_auth();
         }
 
-        let fee_result =
-            FeeCalculator::calculate_standard_fees(escrow.amount as i128, escrow.platform_fee)?;
+        let mut escrow = Self::get_escrow(e)?;
+        validate_cancellation_conditions(&escrow, platform_address, approver)?;
 
-        token_client.transfer(
-            &contract_address,
-            trustless_work_address,
-            &fee_result.trustless_work_fee,
-        );
-        token_client.transfer(
-            &contract_address,
-            &escrow.roles.platform_address,
-            &fee_result.platform_fee,
-        );
+        escrow.flags.cancelled = true;
+        EscrowStorage::save_header(e, &escrow);
+
+        let token_client = TokenClient::new(e, &escrow.trustline.address);
+        let current_balance = token_client.balance(&e.current_contract_address());
+        if current_balance <= 0 {
+            return Ok((escrow, Vec::new(e)));
+        }
 
-        let receiver = Self::get_receiver(&escrow);
-        token_client.transfer(&contract_address, &receiver, &fee_result.receiver_amount);
+        let refunds = Self::return_balance_to_depositors(e, &escrow, current_balance)?;
+        Ok((escrow, refunds))
+    }
 
-        Ok(())
+    /// Funds that reached the contract without going through `fund_escrow` have no recorded
+    /// depositor and are returned to the approver. The recorded deposits are paid back in
+    /// full, so they are cleared afterwards.
+    fn return_balance_to_depositors(
+        e: &Env,
+        escrow: &Escrow,
+        amount: i128,
+    ) -> Result<Vec<Deposit>, Error> {
+        let contract_address = e.current_contract_address();
+        let token_client = TokenClient::new(e, &escrow.trustline.address);
+
+        let mut refunds = DepositManager::calculate_refund_shares(e, amount)?;
+        if refunds.is_empty() {
+            refunds.push_back(Deposit {
+                depositor: escrow.roles.approver.clone(),
+                amount,
+            });
+        }
+
+        for refund in refunds.iter() {
+            if refund.amount > 0 {
+                token_client.transfer(&contract_address, &refund.depositor, &refund.amount);
+            }
+        }
+        DepositManager::clear_deposits(e);
+
+        Ok(refunds)
     }
+
+    /// Sends whatever is left in a released or resolved escrow to the platform: rounding
+    /// remainders and funds that arrived after the escrow was settled.
+    pub fn sweep_dust(e: &Env, platform_address: &Address) -> Result<(Escrow, i128), Error> {
+        platform_address.require_auth();
+        let escrow = Self::get_escrow(e)?;
+        let contract_address = e.current_contract_address();
+        let token_client = TokenClient::new(e, &escrow.trustline.address);
+        let current_balance = token_client.balance(&contract_address);
+
+        validate_sweep_conditions(&escrow, platform_address, current_balance)?;
+
+        token_client.transfer(&contract_address, platform_address, &current_balance);
+        Ok((escrow, current_balance))
+    }
+
+    /// Applies `escrow_properties` directly and returns the escrow as it was before.
     pub fn change_escrow_properties(
         e: &Env,
         platform_address: &Address,
         escrow_properties: Escrow,
-    ) -> Result<Escrow, ContractError> {
+    ) -> Result<Escrow, Error> {
         platform_address.require_auth();
         let existing_escrow = Self::get_escrow(e)?;
+        let contract_balance = Self::validate_property_change(
+            e,
+            &existing_escrow,
+            &escrow_properties,
+            platform_address,
+        )?;
+        validate_direct_update_conditions(&existing_escrow, &escrow_properties, contract_balance)?;
+
+        Self::clear_replaced_milestones(e, &existing_escrow, &escrow_properties);
+        EscrowStorage::save(e, &escrow_properties);
+        Ok(existing_escrow)
+    }
+
+    /// Applies a proposal the approver and service provider have both accepted. The
+    /// properties are validated again, since the escrow may have moved on since the proposal.
+    pub fn apply_escrow_update(e: &Env, proposal: &EscrowUpdateProposal) -> Result<(), Error> {
+        let existing_escrow = Self::get_escrow(e)?;
+        Self::validate_property_change(e, &existing_escrow, &proposal.escrow, &proposal.proposer)?;
+
+        Self::clear_replaced_milestones(e, &existing_escrow, &proposal.escrow);
+        EscrowStorage::save(e, &proposal.escrow);
+        Ok(())
+    }
+
+    /// What `new_escrow` changes compared to `existing_escrow`, as published when an update
+    /// is proposed or applied.
+    pub fn get_changes(e: &Env, existing_escrow: &Escrow, new_escrow: &Escrow) -> EscrowChanges {
+        let (existing, new) = (existing_escrow, new_escrow);
+        let mut properties: Vec<EscrowPropertyChange> = Vec::new(e);
+        let mut record = |changed: bool, change: EscrowPropertyChange| {
+            if changed {
+                properties.push_back(change);
+            }
+        };
+        record(
+            existing.engagement_id != new.engagement_id,
+            EscrowPropertyChange::EngagementId(new.engagement_id.clone()),
+        );
+        record(
+            existing.title != new.title,
+            EscrowPropertyChange::Title(new.title.clone()),
+        );
+        record(
+            existing.roles != new.roles,
+            EscrowPropertyChange::Roles(new.roles.clone()),
+        );
+        record(
+            existing.description != new.description,
+            EscrowPropertyChange::Description(new.description.clone()),
+        );
+        record(
+            existing.amount != new.amount,
+            EscrowPropertyChange::Amount(new.amount),
+        );
+        record(
+            existing.platform_fee != new.platform_fee,
+            EscrowPropertyChange::PlatformFee(new.platform_fee),
+        );
+        record(
+            existing.flags != new.flags,
+            EscrowPropertyChange::Flags(new.flags.clone()),
+        );
+        record(
+            existing.trustline != new.trustline,
+            EscrowPropertyChange::Trustline(new.trustline.clone()),
+        );
+        record(
+            existing.receiver_memo != new.receiver_memo,
+            EscrowPropertyChange::ReceiverMemo(new.receiver_memo),
+        );
+        record(
+            existing.deadline != new.deadline,
+            EscrowPropertyChange::Deadline(new.deadline.clone()),
+        );
+        record(
+            existing.payees != new.payees,
+            EscrowPropertyChange::Payees(new.payees.clone()),
+        );
+        record(
+            existing.co_approvers != new.co_approvers,
+            EscrowPropertyChange::CoApprovers(new.co_approvers.clone()),
+        );
+        record(
+            existing.approval_threshold != new.approval_threshold,
+            EscrowPropertyChange::ApprovalThreshold(new.approval_threshold),
+        );
+        record(
+            existing.arbitration != new.arbitration,
+            EscrowPropertyChange::Arbitration(new.arbitration.clone()),
+        );
+        record(
+            existing.appeal != new.appeal,
+            EscrowPropertyChange::Appeal(new.appeal.clone()),
+        );
+        record(
+            existing.review_period != new.review_period,
+            EscrowPropertyChange::ReviewPeriod(new.review_period),
+        );
+
+        let mut milestones = Map::new(e);
+        for (index, milestone) in new.milestones.iter().enumerate() {
+            if existing.milestones.get(index as u32).as_ref() != Some(&milestone) {
+                milestones.set(index as u32, milestone);
+            }
+        }
+
+        EscrowChanges {
+            properties,
+            milestones,
+            milestone_count: new.milestones.len(),
+        }
+    }
+
+    /// Drops the approvals and submission of every milestone that `new_escrow` changes or
+    /// removes, since they were given for the previous terms. Its evidence log is kept and
+    /// the milestone that takes the index starts a new one.
+    fn clear_replaced_milestones(e: &Env, existing_escrow: &Escrow, new_escrow: &Escrow) {
+        for (index, milestone) in existing_escrow.milestones.iter().enumerate() {
+            if new_escrow.milestones.get(index as u32) != Some(milestone) {
+                EvidenceManager::start_new_revision(e, index as u32);
+                ApprovalManager::clear_approvals(e, index as u32);
+                ReviewManager::clear_submission(e, index as u32);
+            }
+        }
+    }
+
+    /// Checks `escrow_properties` against the stored escrow and returns the contract balance.
+    pub fn validate_property_change(
+        e: &Env,
+        existing_escrow: &Escrow,
+        escrow_properties: &Escrow,
+        platform_address: &Address,
+    ) -> Result<i128, Error> {
         let token_client = TokenClient::new(e, &existing_escrow.trustline.address);
         let contract_balance = token_client.balance(&e.current_contract_address());
 
         validate_escrow_property_change_conditions(
-            &existing_escrow,
-            &escrow_properties,
+            e,
+            existing_escrow,
+            escrow_properties,
             platform_address,
             contract_balance,
+            ProtocolManager::get_protocol_fee_bps(e),
         )?;
 
-        e.storage()
-            .instance()
-            .set(&DataKey::Escrow, &escrow_properties);
-        Ok(escrow_properties)
+        Ok(contract_balance)
     }
 
     pub fn get_multiple_escrow_balances(
         e: &Env,
         addresses: Vec<Address>,
-    ) -> Result<Vec<AddressBalance>, ContractError> {
+    ) -> Result<Vec<AddressBalance>, Error> {
         const MAX_ESCROWS: u32 = 20;
         if addresses.len() > MAX_ESCROWS {
-            return Err(ContractError::TooManyEscrowsRequested);
+            return Err(ContractError::TooManyEscrowsRequested.into());
         }
 
         let mut balances: Vec<AddressBalance> = Vec::new(e);
@@ -130,17 +424,42 @@ impl EscrowManager {
         Ok(balances)
     }
 
-    pub fn get_escrow_by_contract_id(
-        e: &Env,
-        contract_id: &Address,
-    ) -> Result<Escrow, ContractError> {
+    pub fn get_escrow_by_contract_id(e: &Env, contract_id: &Address) -> Result<Escrow, Error> {
         Ok(e.invoke_contract::<Escrow>(contract_id, &Symbol::new(e, "get_escrow"), Vec::new(e)))
     }
 
-    pub fn get_escrow(e: &Env) -> Result<Escrow, ContractError> {
-        Ok(e.storage()
-            .instance()
-            .get(&DataKey::Escrow)
-            .ok_or(ContractError::EscrowNotFound)?)
+    pub fn get_escrow(e: &Env) -> Result<Escrow, Error> {
+        EscrowStorage::load(e)
+    }
+
+    /// Extends the TTL of the escrow header and of the persistent entries that belong to the
+    /// escrow as a whole. Each milestone's entries are extended by `extend_milestone_ttl`,
+    /// so the work per call stays bounded whatever the number of milestones.
+    pub fn extend_ttl(e: &Env, threshold: u32, extend_to: u32) {
+        EscrowStorage::extend_ttl(e, threshold, extend_to);
+        DepositManager::extend_ttl(e, threshold, extend_to);
+        ProposalManager::extend_ttl(e, threshold, extend_to);
+        ArbitrationManager::extend_ttl(e, DisputeTarget::Escrow, threshold, extend_to);
+        AppealManager::extend_ttl(e, DisputeTarget::Escrow, threshold, extend_to);
+        DisputeManager::extend_ttl(e, threshold, extend_to);
+    }
+
+    /// Extends the TTL of the milestone's entry and of the persistent entries kept for it.
+    pub fn extend_milestone_ttl(
+        e: &Env,
+        escrow: &Escrow,
+        milestone_index: i128,
+        threshold: u32,
+        extend_to: u32,
+    ) -> Result<(), Error> {
+        let index = validate_milestone_index(escrow, milestone_index)?;
+        EscrowStorage::extend_milestone_ttl(e, index, threshold, extend_to);
+        EvidenceManager::extend_ttl(e, index, threshold, extend_to);
+        ApprovalManager::extend_ttl(e, index, threshold, extend_to);
+        ReviewManager::extend_ttl(e, index, threshold, extend_to);
+        let target = DisputeTarget::Milestone(index);
+        ArbitrationManager::extend_ttl(e, target, threshold, extend_to);
+        AppealManager::extend_ttl(e, target, threshold, extend_to);
+        Ok(())
     }
 }
@@ -0,0 +1,116 @@
+use soroban_sdk::{Address, Env, Error, Vec};
+
+use crate::error::WorkflowError;
+use crate::storage::types::{DataKey, Evidence, EvidenceSubmission};
+
+/// Entries a milestone's evidence log may hold, which bounds the entries that
+/// `extend_milestone_ttl` has to keep alive for a milestone.
+///
+/// Each revision of a milestone has its own log. The revision is bumped whenever an update
+/// replaces or removes the milestone at an index, so evidence given for earlier terms stays
+/// on record without being attributed to the milestone that now holds the index.
+const MAX_EVIDENCE_ENTRIES: u32 = 20;
+
+pub struct EvidenceManager;
+
+impl EvidenceManager {
+    /// Appends a new entry to the evidence log of the milestone's current revision. Entries
+    /// are never overwritten, so the log keeps every submission made for the milestone.
+    pub fn record_evidence(
+        e: &Env,
+        milestone_index: u32,
+        submitter: &Address,
+        submission: EvidenceSubmission,
+    ) -> Result<Evidence, Error> {
+        let storage = e.storage().persistent();
+        let revision = Self::get_revision(e, milestone_index);
+        let count = Self::get_evidence_count(e, milestone_index, revision);
+        if count >= MAX_EVIDENCE_ENTRIES {
+            return Err(WorkflowError::TooManyEvidenceEntries.into());
+        }
+
+        let evidence = Evidence {
+            submitter: submitter.clone(),
+            timestamp: e.ledger().timestamp(),
+            uri: submission.uri,
+            hash: submission.hash,
+        };
+
+        let key = DataKey::Evidence(milestone_index, revision, count);
+        storage.set(&key, &evidence);
+        storage.set(
+            &DataKey::EvidenceCount(milestone_index, revision),
+            &(count + 1),
+        );
+        Ok(evidence)
+    }
+
+    pub fn get_revision(e: &Env, milestone_index: u32) -> u32 {
+        e.storage()
+            .persistent()
+            .get(&DataKey::MilestoneRevision(milestone_index))
+            .unwrap_or(0)
+    }
+
+    /// Starts an empty log for the milestone that replaces the one at `milestone_index`.
+    /// The logs of earlier revisions are left untouched.
+    pub fn start_new_revision(e: &Env, milestone_index: u32) {
+        let revision = Self::get_revision(e, milestone_index);
+        e.storage().persistent().set(
+            &DataKey::MilestoneRevision(milestone_index),
+            &(revision + 1),
+        );
+    }
+
+    pub fn get_evidence_count(e: &Env, milestone_index: u32, revision: u32) -> u32 {
+        e.storage()
+            .persistent()
+            .get(&DataKey::EvidenceCount(milestone_index, revision))
+            .unwrap_or(0)
+    }
+
+    /// Returns up to `limit` entries of the evidence log of the milestone's `revision`,
+    /// oldest first, starting at position `start`.
+    pub fn get_evidence(
+        e: &Env,
+        milestone_index: u32,
+        revision: u32,
+        start: u32,
+        limit: u32,
+    ) -> Vec<Evidence> {
+        let storage = e.storage().persistent();
+        let count = Self::get_evidence_count(e, milestone_index, revision);
+        let end = start.saturating_add(limit).min(count);
+
+        let mut entries: Vec<Evidence> = Vec::new(e);
+        for position in start..end {
+            let key = DataKey::Evidence(milestone_index, revision, position);
+            if let Some(evidence) = storage.get(&key) {
+                entries.push_back(evidence);
+            }
+        }
+        entries
+    }
+
+    /// Keeps the revision counter and the log of the current revision alive.
+    pub fn extend_ttl(e: &Env, milestone_index: u32, threshold: u32, extend_to: u32) {
+        let storage = e.storage().persistent();
+        let revision_key = DataKey::MilestoneRevision(milestone_index);
+        if storage.has(&revision_key) {
+            storage.extend_ttl(&revision_key, threshold, extend_to);
+        }
+
+        let revision = Self::get_revision(e, milestone_index);
+        let count = Self::get_evidence_count(e, milestone_index, revision);
+        if count == 0 {
+            return;
+        }
+
+        let count_key = DataKey::EvidenceCount(milestone_index, revision);
+        storage.extend_ttl(&count_key, threshold, extend_to);
+        for position in 0..count {
+            let key = DataKey::Evidence(milestone_index, revision, position);
+            storage.extend_ttl(&key, threshold, extend_to);
+        }
+    }
+}
@@ -1,74 +1,311 @@
 use crate::error::ContractError;
-use crate::storage::types::DataKey;
-use crate::{core::escrow::EscrowManager, storage::types::Escrow};
-use soroban_sdk::{Address, Env, String};
+use crate::storage::escrow::EscrowStorage;
+use crate::storage::types::{Evidence, EvidenceSubmission, MilestoneStatus};
+use crate::{
+    core::{
+        approval::ApprovalManager, escrow::EscrowManager, evidence::EvidenceManager,
+        review::ReviewManager,
+    },
+    storage::types::Escrow,
+};
+use soroban_sdk::{Address, Env, Error, String, Vec};
 
 use super::validators::milestone::{
-    validate_milestone_flag_change_conditions, validate_milestone_status_change_conditions,
+    validate_approval_withdrawal_conditions, validate_auto_approval_conditions,
+    validate_milestone_approval_conditions, validate_milestone_batch, validate_milestone_index,
+    validate_milestone_rejection_conditions, validate_milestone_status_change_conditions,
 };
 
+/// The status a milestone had before a change, and the evidence recorded with the change.
+pub type AppliedStatusChange = (MilestoneStatus, Option<Evidence>);
+
 pub struct MilestoneManager;
 
 impl MilestoneManager {
     pub fn change_milestone_status(
         e: &Env,
         milestone_index: i128,
-        new_status: String,
-        new_evidence: Option<String>,
+        new_status: MilestoneStatus,
+        new_evidence: Option<EvidenceSubmission>,
         service_provider: Address,
-    ) -> Result<Escrow, ContractError> {
+    ) -> Result<(Escrow, MilestoneStatus, Option<Evidence>), Error> {
         service_provider.require_auth();
         let mut existing_escrow = EscrowManager::get_escrow(e)?;
+        let index = validate_milestone_index(&existing_escrow, milestone_index)?;
+
+        let (old_status, recorded_evidence) = Self::apply_status_change(
+            e,
+            &mut existing_escrow,
+            index,
+            new_status,
+            new_evidence,
+            &service_provider,
+        )?;
+        EscrowStorage::save_milestone(e, &existing_escrow, index)?;
 
-        validate_milestone_status_change_conditions(&existing_escrow, &service_provider)?;
+        Ok((existing_escrow, old_status, recorded_evidence))
+    }
 
-        let mut milestone_to_update = existing_escrow
+    /// Applies several status changes at once. Every entry is checked against the escrow as
+    /// left by the entries before it, and any invalid entry fails the whole batch. Returns
+    /// the previous status and the evidence recorded for each entry, in order.
+    pub fn change_milestone_statuses(
+        e: &Env,
+        changes: Vec<(u32, MilestoneStatus, Option<EvidenceSubmission>)>,
+        service_provider: Address,
+    ) -> Result<(Escrow, Vec<AppliedStatusChange>), Error> {
+        service_provider.require_auth();
+        let mut existing_escrow = EscrowManager::get_escrow(e)?;
+
+        let mut milestone_indexes = Vec::new(e);
+        for (milestone_index, _, _) in changes.iter() {
+            milestone_indexes.push_back(milestone_index);
+        }
+        validate_milestone_batch(&milestone_indexes)?;
+
+        let mut applied_changes = Vec::new(e);
+        for (milestone_index, new_status, new_evidence) in changes.iter() {
+            applied_changes.push_back(Self::apply_status_change(
+                e,
+                &mut existing_escrow,
+                milestone_index,
+                new_status,
+                new_evidence,
+                &service_provider,
+            )?);
+        }
+        EscrowStorage::save_milestones(e, &existing_escrow, &milestone_indexes)?;
+
+        Ok((existing_escrow, applied_changes))
+    }
+
+    /// Validates the change and applies it to `escrow`, returning the previous status. The
+    /// milestone itself is left for the caller to save.
+    fn apply_status_change(
+        e: &Env,
+        escrow: &mut Escrow,
+        milestone_index: u32,
+        new_status: MilestoneStatus,
+        new_evidence: Option<EvidenceSubmission>,
+        service_provider: &Address,
+    ) -> Result<AppliedStatusChange, Error> {
+        let mut milestone_to_update = escrow
             .milestones
-            .get(milestone_index as u32)
+            .get(milestone_index)
             .ok_or(ContractError::InvalidMileStoneIndex)?;
 
-        if let Some(evidence) = new_evidence {
-            milestone_to_update.evidence = evidence;
-        }
+        validate_milestone_status_change_conditions(
+            escrow,
+            &milestone_to_update,
+            &new_status,
+            service_provider,
+        )?;
+
+        let recorded_evidence = match new_evidence {
+            Some(submission) => {
+                milestone_to_update.evidence = submission.uri.clone();
+                Some(EvidenceManager::record_evidence(
+                    e,
+                    milestone_index,
+                    service_provider,
+                    submission,
+                )?)
+            }
+            None => None,
+        };
 
+        // A resubmitted milestone is reviewed afresh, so earlier approvals no longer count
+        if new_status == MilestoneStatus::Submitted {
+            ReviewManager::record_submission(e, milestone_index);
+            ApprovalManager::clear_approvals(e, milestone_index);
+        }
+        let old_status = milestone_to_update.status;
         milestone_to_update.status = new_status;
 
-        existing_escrow
-            .milestones
-            .set(milestone_index as u32, milestone_to_update);
-        e.storage()
-            .instance()
-            .set(&DataKey::Escrow, &existing_escrow);
+        escrow.milestones.set(milestone_index, milestone_to_update);
 
-        Ok(existing_escrow)
+        Ok((old_status, recorded_evidence))
     }
 
+    /// Reads the evidence log of `revision`, or of the milestone's current revision when
+    /// `revision` is `None`.
+    pub fn get_milestone_evidence(
+        e: &Env,
+        milestone_index: i128,
+        revision: Option<u32>,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<Evidence>, Error> {
+        let escrow = EscrowManager::get_escrow(e)?;
+        let index = validate_milestone_index(&escrow, milestone_index)?;
+        let revision = revision.unwrap_or_else(|| EvidenceManager::get_revision(e, index));
+        Ok(EvidenceManager::get_evidence(
+            e, index, revision, start, limit,
+        ))
+    }
+
+    pub fn get_milestone_revision(e: &Env, milestone_index: i128) -> Result<u32, Error> {
+        let escrow = EscrowManager::get_escrow(e)?;
+        let index = validate_milestone_index(&escrow, milestone_index)?;
+        Ok(EvidenceManager::get_revision(e, index))
+    }
+
+    /// Records the approver's approval and returns the escrow along with the number of
+    /// approvals the milestone holds. The milestone is only marked approved once that number
+    /// reaches the escrow's approval threshold.
     pub fn change_milestone_approved_flag(
         e: &Env,
         milestone_index: i128,
         approver: Address,
-    ) -> Result<Escrow, ContractError> {
+    ) -> Result<(Escrow, u32), Error> {
         approver.require_auth();
         let mut existing_escrow = EscrowManager::get_escrow(e)?;
+        let index = validate_milestone_index(&existing_escrow, milestone_index)?;
+
+        let approval_count = Self::apply_approval(e, &mut existing_escrow, index, &approver)?;
+        if approval_count >= existing_escrow.approval_threshold {
+            EscrowStorage::save_milestone(e, &existing_escrow, index)?;
+        }
+
+        Ok((existing_escrow, approval_count))
+    }
+
+    /// Records the approver's approval of several milestones at once, so a milestone may
+    /// depend on one approved earlier in the same batch. Any invalid index fails the whole
+    /// batch. Returns the number of approvals of each milestone, in order.
+    pub fn approve_milestones(
+        e: &Env,
+        milestone_indexes: Vec<u32>,
+        approver: Address,
+    ) -> Result<(Escrow, Vec<u32>), Error> {
+        approver.require_auth();
+        let mut existing_escrow = EscrowManager::get_escrow(e)?;
+        validate_milestone_batch(&milestone_indexes)?;
+
+        let mut approval_counts = Vec::new(e);
+        let mut approved_indexes = Vec::new(e);
+        for milestone_index in milestone_indexes.iter() {
+            let approval_count =
+                Self::apply_approval(e, &mut existing_escrow, milestone_index, &approver)?;
+            if approval_count >= existing_escrow.approval_threshold {
+                approved_indexes.push_back(milestone_index);
+            }
+            approval_counts.push_back(approval_count);
+        }
+        EscrowStorage::save_milestones(e, &existing_escrow, &approved_indexes)?;
+
+        Ok((existing_escrow, approval_counts))
+    }
 
-        let mut milestone_to_update = existing_escrow
+    /// Validates and records the approval, and marks the milestone approved in `escrow`
+    /// when it reaches the threshold. The milestone itself is left for the caller to save.
+    fn apply_approval(
+        e: &Env,
+        escrow: &mut Escrow,
+        milestone_index: u32,
+        approver: &Address,
+    ) -> Result<u32, Error> {
+        let mut milestone_to_update = escrow
             .milestones
-            .get(milestone_index as u32)
+            .get(milestone_index)
             .ok_or(ContractError::InvalidMileStoneIndex)?;
 
-        validate_milestone_flag_change_conditions(
+        validate_milestone_approval_conditions(escrow, &milestone_to_update, approver)?;
+
+        let approvals = ApprovalManager::add_approval(e, milestone_index, approver)?;
+        let approval_count = ApprovalManager::count_approvals(escrow, &approvals);
+        if approval_count < escrow.approval_threshold {
+            return Ok(approval_count);
+        }
+
+        milestone_to_update.approved = true;
+        milestone_to_update.status = MilestoneStatus::Approved;
+
+        escrow.milestones.set(milestone_index, milestone_to_update);
+        ReviewManager::clear_submission(e, milestone_index);
+
+        Ok(approval_count)
+    }
+
+    /// Approves a submitted milestone whose review period has elapsed without the approver
+    /// acting on it. Anyone may call it. Returns the escrow and the time the milestone was
+    /// submitted.
+    pub fn auto_approve(e: &Env, milestone_index: i128) -> Result<(Escrow, u64), Error> {
+        let mut existing_escrow = EscrowManager::get_escrow(e)?;
+        let index = validate_milestone_index(&existing_escrow, milestone_index)?;
+
+        let mut milestone_to_update = existing_escrow.milestones.get(index).unwrap();
+
+        let submitted_at = ReviewManager::get_submitted_at(e, index);
+        validate_auto_approval_conditions(
             &existing_escrow,
             &milestone_to_update,
-            &approver,
+            submitted_at,
+            e.ledger().timestamp(),
         )?;
+
         milestone_to_update.approved = true;
+        milestone_to_update.status = MilestoneStatus::Approved;
 
-        existing_escrow
-            .milestones
-            .set(milestone_index as u32, milestone_to_update);
-        e.storage()
-            .instance()
-            .set(&DataKey::Escrow, &existing_escrow);
+        existing_escrow.milestones.set(index, milestone_to_update);
+        EscrowStorage::save_milestone(e, &existing_escrow, index)?;
+        ReviewManager::clear_submission(e, index);
+
+        Ok((existing_escrow, submitted_at.unwrap_or_default()))
+    }
+
+    /// Takes back an approval given to a milestone that has not reached the threshold yet,
+    /// and returns the number of approvals left.
+    pub fn withdraw_milestone_approval(
+        e: &Env,
+        milestone_index: i128,
+        approver: Address,
+    ) -> Result<(Escrow, u32), Error> {
+        approver.require_auth();
+        let existing_escrow = EscrowManager::get_escrow(e)?;
+        let index = validate_milestone_index(&existing_escrow, milestone_index)?;
+
+        let milestone = existing_escrow.milestones.get(index).unwrap();
+
+        validate_approval_withdrawal_conditions(&existing_escrow, &milestone, &approver)?;
+
+        let approvals = ApprovalManager::remove_approval(e, index, &approver)?;
+        let approval_count = ApprovalManager::count_approvals(&existing_escrow, &approvals);
+        Ok((existing_escrow, approval_count))
+    }
+
+    pub fn get_milestone_approvals(e: &Env, milestone_index: i128) -> Result<Vec<Address>, Error> {
+        let escrow = EscrowManager::get_escrow(e)?;
+        let index = validate_milestone_index(&escrow, milestone_index)?;
+        Ok(ApprovalManager::get_approvals(e, index))
+    }
+
+    pub fn reject_milestone(
+        e: &Env,
+        milestone_index: i128,
+        approver: Address,
+        reason: String,
+    ) -> Result<Escrow, Error> {
+        approver.require_auth();
+        let mut existing_escrow = EscrowManager::get_escrow(e)?;
+        let index = validate_milestone_index(&existing_escrow, milestone_index)?;
+
+        let mut milestone_to_update = existing_escrow.milestones.get(index).unwrap();
+
+        validate_milestone_rejection_conditions(
+            &existing_escrow,
+            &milestone_to_update,
+            &approver,
+            &reason,
+        )?;
+        milestone_to_update.status = MilestoneStatus::Rejected;
+        milestone_to_update.rejection_reason = Some(reason);
+        milestone_to_update.rejection_count += 1;
+
+        existing_escrow.milestones.set(index, milestone_to_update);
+        EscrowStorage::save_milestone(e, &existing_escrow, index)?;
+        ApprovalManager::clear_approvals(e, index);
+        ReviewManager::clear_submission(e, index);
 
         Ok(existing_escrow)
     }
@@ -0,0 +1,173 @@
+use soroban_sdk::token::Client as TokenClient;
+use soroban_sdk::{Address, Env, Error, Map, Vec};
+
+use crate::core::escrow::EscrowManager;
+use crate::core::protocol::ProtocolManager;
+use crate::modules::{
+    fee::{FeeCalculator, FeeCalculatorTrait, BASIS_POINTS_DENOMINATOR},
+    math::{BasicArithmetic, BasicMath, SafeArithmetic, SafeMath},
+};
+use crate::storage::types::{Escrow, Payout, PayoutQuote};
+
+/// Computes who gets what out of a release or a dispute resolution. Quotes are pure
+/// calculations, so the view functions and the actual payouts always agree.
+pub struct PayoutManager;
+
+impl PayoutManager {
+    pub fn quote_release(e: &Env, escrow: &Escrow, amount: i128) -> Result<PayoutQuote, Error> {
+        let protocol_config = ProtocolManager::get_protocol_config(e);
+        let fee_result = FeeCalculator::calculate_standard_fees(
+            amount,
+            protocol_config.fee_bps,
+            escrow.platform_fee,
+        )?;
+
+        let mut payouts: Vec<Payout> = Vec::new(e);
+        Self::push_receiver_payouts(&mut payouts, escrow, amount, fee_result.receiver_amount)?;
+
+        Ok(PayoutQuote {
+            protocol_fee: fee_result.trustless_work_fee,
+            platform_fee: fee_result.platform_fee,
+            payouts,
+        })
+    }
+
+    /// Fees are charged on `total` and each recipient pays its share of them.
+    ///
+    /// Rounding policy: fee shares are proportional to each recipient's amount and rounded
+    /// down, except for the recipient with the largest amount (the first one in address order
+    /// on a tie), who bears the remaining fees. The fee shares therefore add up to exactly the
+    /// fees charged, and the quote adds up to exactly `total`.
+    pub fn quote_distribution(
+        e: &Env,
+        escrow: &Escrow,
+        distributions: &Map<Address, i128>,
+        total: i128,
+    ) -> Result<PayoutQuote, Error> {
+        let protocol_config = ProtocolManager::get_protocol_config(e);
+        let fee_result = FeeCalculator::calculate_standard_fees(
+            total,
+            protocol_config.fee_bps,
+            escrow.platform_fee,
+        )?;
+        let total_fees =
+            BasicMath::safe_add(fee_result.trustless_work_fee, fee_result.platform_fee)?;
+
+        let mut largest_index = 0;
+        let mut largest_amount = 0;
+        for (index, (_addr, amount)) in distributions.iter().enumerate() {
+            if amount > largest_amount {
+                largest_index = index;
+                largest_amount = amount;
+            }
+        }
+
+        let mut rounded_fees: i128 = 0;
+        for (index, (_addr, amount)) in distributions.iter().enumerate() {
+            if index != largest_index {
+                let fee_share = SafeMath::safe_share(amount, total_fees, total)?;
+                rounded_fees = BasicMath::safe_add(rounded_fees, fee_share)?;
+            }
+        }
+
+        let mut payouts: Vec<Payout> = Vec::new(e);
+        for (index, (addr, amount)) in distributions.iter().enumerate() {
+            let fee_share = if index == largest_index {
+                BasicMath::safe_sub(total_fees, rounded_fees)?
+            } else {
+                SafeMath::safe_share(amount, total_fees, total)?
+            };
+            let net_amount = BasicMath::safe_sub(amount, fee_share)?;
+            if addr == escrow.roles.receiver {
+                Self::push_receiver_payouts(&mut payouts, escrow, amount, net_amount)?;
+            } else {
+                payouts.push_back(Payout {
+                    recipient: addr,
+                    gross: amount,
+                    fee_share,
+                    amount: net_amount,
+                });
+            }
+        }
+
+        Ok(PayoutQuote {
+            protocol_fee: fee_result.trustless_work_fee,
+            platform_fee: fee_result.platform_fee,
+            payouts,
+        })
+    }
+
+    pub fn execute(e: &Env, escrow: &Escrow, quote: &PayoutQuote) -> Result<(), Error> {
+        let contract_address = e.current_contract_address();
+        let token_client = TokenClient::new(e, &escrow.trustline.address);
+        let protocol_config = ProtocolManager::get_protocol_config(e);
+
+        if quote.protocol_fee > 0 {
+            token_client.transfer(
+                &contract_address,
+                &protocol_config.treasury,
+                &quote.protocol_fee,
+            );
+        }
+        if quote.platform_fee > 0 {
+            token_client.transfer(
+                &contract_address,
+                &escrow.roles.platform_address,
+                &quote.platform_fee,
+            );
+        }
+        for payout in quote.payouts.iter() {
+            if payout.amount > 0 {
+                token_client.transfer(&contract_address, &payout.recipient, &payout.amount);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Splits what is owed to the receiver side between the payees, in proportion to their
+    /// shares. Both `gross` and the net `amount` are split that way: every share is rounded
+    /// down and the last payee receives the remainder, so the shares always add up to them.
+    /// Each payee's fee share is the difference. Without payees, all of it goes to the
+    /// receiver.
+    fn push_receiver_payouts(
+        payouts: &mut Vec<Payout>,
+        escrow: &Escrow,
+        gross: i128,
+        amount: i128,
+    ) -> Result<(), Error> {
+        if escrow.payees.is_empty() {
+            payouts.push_back(Payout {
+                recipient: EscrowManager::get_receiver(escrow),
+                gross,
+                fee_share: BasicMath::safe_sub(gross, amount)?,
+                amount,
+            });
+            return Ok(());
+        }
+
+        let mut remaining_gross = gross;
+        let mut remaining = amount;
+        let last_index = escrow.payees.len() - 1;
+        for (index, payee) in escrow.payees.iter().enumerate() {
+            let (gross_share, share) = if index as u32 == last_index {
+                (remaining_gross, remaining)
+            } else {
+                (
+                    SafeMath::safe_mul_div(gross, payee.share_bps, BASIS_POINTS_DENOMINATOR)?,
+                    SafeMath::safe_mul_div(amount, payee.share_bps, BASIS_POINTS_DENOMINATOR)?,
+                )
+            };
+            remaining_gross = BasicMath::safe_sub(remaining_gross, gross_share)?;
+            remaining = BasicMath::safe_sub(remaining, share)?;
+            payouts.push_back(Payout {
+                recipient: payee.address,
+                gross: gross_share,
+                fee_share: BasicMath::safe_sub(gross_share, share)?,
+                amount: share,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use soroban_sdk::{Address, Env, Error};
+
+use crate::core::escrow::EscrowManager;
+use crate::core::validators::escrow::validate_update_acceptance_conditions;
+use crate::error::WorkflowError;
+use crate::storage::types::{DataKey, Escrow, EscrowUpdateProposal};
+
+/// How long a proposal stays open for the approver and service provider to accept it.
+pub const UPDATE_PROPOSAL_VALIDITY_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+pub struct ProposalManager;
+
+impl ProposalManager {
+    /// Stages new escrow properties and returns the current escrow with the proposal. A new
+    /// proposal replaces any pending one, along with the acceptances it had collected.
+    pub fn propose_escrow_update(
+        e: &Env,
+        platform_address: &Address,
+        escrow_properties: Escrow,
+    ) -> Result<(Escrow, EscrowUpdateProposal), Error> {
+        platform_address.require_auth();
+        let existing_escrow = EscrowManager::get_escrow(e)?;
+        EscrowManager::validate_property_change(
+            e,
+            &existing_escrow,
+            &escrow_properties,
+            platform_address,
+        )?;
+
+        let proposal = EscrowUpdateProposal {
+            escrow: escrow_properties,
+            proposer: platform_address.clone(),
+            expires_at: e
+                .ledger()
+                .timestamp()
+                .saturating_add(UPDATE_PROPOSAL_VALIDITY_SECONDS),
+            approver_accepted: false,
+            service_provider_accepted: false,
+        };
+        e.storage()
+            .persistent()
+            .set(&DataKey::UpdateProposal, &proposal);
+        Ok((existing_escrow, proposal))
+    }
+
+    /// Records the signer's acceptance. Once both the approver and the service provider have
+    /// accepted, the proposal is applied and removed. Returns the escrow as it was before,
+    /// the proposal and whether it was applied.
+    pub fn accept_escrow_update(
+        e: &Env,
+        signer: &Address,
+    ) -> Result<(Escrow, EscrowUpdateProposal, bool), Error> {
+        signer.require_auth();
+        let mut proposal = Self::get_update_proposal(e)?;
+        let escrow = EscrowManager::get_escrow(e)?;
+        validate_update_acceptance_conditions(e, &escrow, &proposal, signer)?;
+
+        if signer == &escrow.roles.approver {
+            proposal.approver_accepted = true;
+        }
+        if signer == &escrow.roles.service_provider {
+            proposal.service_provider_accepted = true;
+        }
+
+        let storage = e.storage().persistent();
+        if !(proposal.approver_accepted && proposal.service_provider_accepted) {
+            storage.set(&DataKey::UpdateProposal, &proposal);
+            return Ok((escrow, proposal, false));
+        }
+
+        EscrowManager::apply_escrow_update(e, &proposal)?;
+        Self::discard_update_proposal(e);
+        Ok((escrow, proposal, true))
+    }
+
+    pub fn get_update_proposal(e: &Env) -> Result<EscrowUpdateProposal, Error> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::UpdateProposal)
+            .ok_or(WorkflowError::UpdateProposalNotFound.into())
+    }
+
+    pub fn discard_update_proposal(e: &Env) {
+        e.storage().persistent().remove(&DataKey::UpdateProposal);
+    }
+
+    pub fn extend_ttl(e: &Env, threshold: u32, extend_to: u32) {
+        let storage = e.storage().persistent();
+        if storage.has(&DataKey::UpdateProposal) {
+            storage.extend_ttl(&DataKey::UpdateProposal, threshold, extend_to);
+        }
+    }
+}
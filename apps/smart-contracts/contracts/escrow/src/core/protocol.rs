@@ -0,0 +1,72 @@
+use soroban_sdk::{Address, Env, Error};
+
+use crate::error::{ContractError, FundsError};
+use crate::storage::escrow::EscrowStorage;
+use crate::storage::types::{DataKey, ProtocolConfig};
+
+use super::validators::protocol::validate_protocol_config_conditions;
+
+pub const DEFAULT_PROTOCOL_FEE_BPS: u32 = 30;
+
+/// Highest protocol fee the admin can set: 1% of every payout. The fee is charged on top of
+/// the platform fee, so it stays small enough that it cannot take over an escrow's value.
+pub const MAX_PROTOCOL_FEE_BPS: u32 = 100;
+
+pub struct ProtocolManager;
+
+impl ProtocolManager {
+    /// Stores the protocol admin and treasury passed to the constructor when the contract is
+    /// deployed, with the default protocol fee. The admin must sign the deployment, so no
+    /// deployer can appoint an admin that did not agree to it.
+    pub fn initialize(e: &Env, admin: &Address, treasury: &Address) {
+        admin.require_auth();
+        let protocol_config = ProtocolConfig {
+            fee_bps: DEFAULT_PROTOCOL_FEE_BPS,
+            treasury: treasury.clone(),
+        };
+        e.storage().instance().set(&DataKey::Admin, admin);
+        e.storage()
+            .instance()
+            .set(&DataKey::ProtocolConfig, &protocol_config);
+    }
+
+    /// Replaces the protocol fee and treasury. Only the protocol admin may call it, and only
+    /// before the escrow is initialized: from then on the config is part of the escrow's
+    /// terms and every payout uses it.
+    pub fn set_protocol_config(
+        e: &Env,
+        admin: &Address,
+        protocol_config: &ProtocolConfig,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        if admin != &Self::get_admin(e) {
+            return Err(FundsError::OnlyAdminCanExecuteThisFunction.into());
+        }
+        if EscrowStorage::has(e) {
+            return Err(ContractError::EscrowAlreadyInitialized.into());
+        }
+        validate_protocol_config_conditions(protocol_config)?;
+
+        e.storage()
+            .instance()
+            .set(&DataKey::ProtocolConfig, protocol_config);
+        Ok(())
+    }
+
+    /// The admin passed to the constructor.
+    pub fn get_admin(e: &Env) -> Address {
+        e.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// The config set by the constructor or by the admin before the escrow was initialized.
+    pub fn get_protocol_config(e: &Env) -> ProtocolConfig {
+        e.storage()
+            .instance()
+            .get(&DataKey::ProtocolConfig)
+            .unwrap()
+    }
+
+    pub fn get_protocol_fee_bps(e: &Env) -> u32 {
+        Self::get_protocol_config(e).fee_bps
+    }
+}
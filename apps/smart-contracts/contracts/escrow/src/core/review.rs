@@ -0,0 +1,38 @@
+use soroban_sdk::Env;
+
+use crate::storage::types::DataKey;
+
+/// Keeps when each milestone was last submitted, which starts its review period.
+pub struct ReviewManager;
+
+impl ReviewManager {
+    /// Starts the review period of the milestone over. A resubmission gives the approver
+    /// the full period to review the new evidence.
+    pub fn record_submission(e: &Env, milestone_index: u32) -> u64 {
+        let submitted_at = e.ledger().timestamp();
+        e.storage()
+            .persistent()
+            .set(&DataKey::MilestoneSubmittedAt(milestone_index), &submitted_at);
+        submitted_at
+    }
+
+    pub fn get_submitted_at(e: &Env, milestone_index: u32) -> Option<u64> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::MilestoneSubmittedAt(milestone_index))
+    }
+
+    pub fn clear_submission(e: &Env, milestone_index: u32) {
+        e.storage()
+            .persistent()
+            .remove(&DataKey::MilestoneSubmittedAt(milestone_index));
+    }
+
+    pub fn extend_ttl(e: &Env, milestone_index: u32, threshold: u32, extend_to: u32) {
+        let storage = e.storage().persistent();
+        let key = DataKey::MilestoneSubmittedAt(milestone_index);
+        if storage.has(&key) {
+            storage.extend_ttl(&key, threshold, extend_to);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use soroban_sdk::{Address, Env, Error};
+
+use crate::core::escrow::EscrowManager;
+use crate::core::proposal::ProposalManager;
+use crate::core::validators::escrow::{
+    validate_role_transfer_conditions, validate_transferred_roles,
+};
+use crate::storage::escrow::EscrowStorage;
+use crate::storage::types::{Escrow, Role, Roles};
+
+pub struct RoleManager;
+
+impl RoleManager {
+    pub fn get_role_holder(roles: &Roles, role: Role) -> Address {
+        match role {
+            Role::Approver => roles.approver.clone(),
+            Role::ServiceProvider => roles.service_provider.clone(),
+            Role::PlatformAddress => roles.platform_address.clone(),
+            Role::ReleaseSigner => roles.release_signer.clone(),
+            Role::DisputeResolver => roles.dispute_resolver.clone(),
+            Role::Receiver => roles.receiver.clone(),
+        }
+    }
+
+    /// Roles that decide where funds go. Handing them off also needs the platform's signature.
+    pub fn is_sensitive_role(role: Role) -> bool {
+        matches!(
+            role,
+            Role::ReleaseSigner | Role::DisputeResolver | Role::Receiver
+        )
+    }
+
+    /// Hands `role` from its current holder to `new_address` and returns the updated escrow
+    /// with the previous holder.
+    /// Works on funded escrows too, since only the role changes. Any pending update proposal
+    /// is dropped, as it was built and accepted against the old roles.
+    pub fn transfer_role(
+        e: &Env,
+        role: Role,
+        new_address: &Address,
+    ) -> Result<(Escrow, Address), Error> {
+        let mut escrow = EscrowManager::get_escrow(e)?;
+        let current_holder = Self::get_role_holder(&escrow.roles, role);
+        validate_role_transfer_conditions(&escrow, role, &current_holder, new_address)?;
+
+        current_holder.require_auth();
+        if Self::is_sensitive_role(role) && current_holder != escrow.roles.platform_address {
+            escrow.roles.platform_address.require_auth();
+        }
+
+        match role {
+            Role::Approver => escrow.roles.approver = new_address.clone(),
+            Role::ServiceProvider => escrow.roles.service_provider = new_address.clone(),
+            Role::PlatformAddress => escrow.roles.platform_address = new_address.clone(),
+            Role::ReleaseSigner => escrow.roles.release_signer = new_address.clone(),
+            Role::DisputeResolver => escrow.roles.dispute_resolver = new_address.clone(),
+            Role::Receiver => escrow.roles.receiver = new_address.clone(),
+        }
+        validate_transferred_roles(&escrow)?;
+
+        EscrowStorage::save_header(e, &escrow);
+        ProposalManager::discard_update_proposal(e);
+        Ok((escrow, current_holder))
+    }
+}
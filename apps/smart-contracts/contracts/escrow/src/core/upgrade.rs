@@ -0,0 +1,79 @@
+use soroban_sdk::{Address, BytesN, Env, Error};
+
+use crate::core::protocol::ProtocolManager;
+use crate::storage::escrow::EscrowStorage;
+use crate::storage::types::DataKey;
+
+use super::validators::upgrade::{validate_migration_conditions, validate_upgrade_conditions};
+
+/// Version of the escrow code in this WASM. Bump it whenever a release changes the layout
+/// of the stored state, and make `migrate` convert the previous layout.
+///
+/// 1: whole escrow stored as a single instance entry (legacy layout).
+/// 2: escrow header in instance storage, one persistent entry per milestone.
+pub const CONTRACT_VERSION: u32 = 2;
+
+/// Escrows initialized before versioning was introduced carry no version entry.
+const LEGACY_VERSION: u32 = 1;
+
+pub struct UpgradeManager;
+
+impl UpgradeManager {
+    /// An escrow without a version entry was written either by the legacy code or by a write
+    /// of this code that moved it out of the legacy layout.
+    pub fn get_stored_version(e: &Env) -> u32 {
+        e.storage()
+            .instance()
+            .get(&DataKey::Version)
+            .unwrap_or_else(|| {
+                if EscrowStorage::is_legacy(e) {
+                    LEGACY_VERSION
+                } else {
+                    CONTRACT_VERSION
+                }
+            })
+    }
+
+    pub fn set_stored_version(e: &Env) {
+        e.storage()
+            .instance()
+            .set(&DataKey::Version, &CONTRACT_VERSION);
+    }
+
+    /// Installs new code for this escrow. Both the platform and the protocol admin must sign.
+    /// The new code takes over once the current invocation finishes, after which `migrate`
+    /// must be called to bring the state up to date.
+    pub fn upgrade(
+        e: &Env,
+        platform_address: &Address,
+        new_wasm_hash: &BytesN<32>,
+    ) -> Result<(), Error> {
+        platform_address.require_auth();
+        ProtocolManager::get_admin(e).require_auth();
+        let stored_platform = EscrowStorage::load_platform_address(e)?;
+        validate_upgrade_conditions(&stored_platform, platform_address)?;
+
+        e.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+        Ok(())
+    }
+
+    /// Post-upgrade hook: moves a legacy escrow to the current layout and returns the
+    /// version it was migrated from. Only the version and the platform address are read
+    /// beforehand, as the legacy entry does not decode with the current types.
+    pub fn migrate(e: &Env, platform_address: &Address) -> Result<u32, Error> {
+        platform_address.require_auth();
+        let stored_platform = EscrowStorage::load_platform_address(e)?;
+        let stored_version = Self::get_stored_version(e);
+        validate_migration_conditions(
+            &stored_platform,
+            platform_address,
+            stored_version,
+            CONTRACT_VERSION,
+        )?;
+
+        EscrowStorage::migrate(e)?;
+        Self::set_stored_version(e);
+        Ok(stored_version)
+    }
+}
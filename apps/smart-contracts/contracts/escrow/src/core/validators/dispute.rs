@@ -1,35 +1,54 @@
-use soroban_sdk::Address;
+use soroban_sdk::{Address, Env, Error, String};
 
 use crate::{
-    error::ContractError,
-    storage::types::{Escrow, Roles},
+    error::{ContractError, DisputeError},
+    storage::types::{
+        Appeal, Arbitration, DisputeVoting, Escrow, Milestone, PendingResolution, Roles,
+    },
 };
 
+use super::escrow::validate_escrow_not_cancelled;
+
 #[inline]
 pub fn validate_dispute_resolution_conditions(
+    e: &Env,
     escrow: &Escrow,
     dispute_resolver: &Address,
+    voting: &Option<DisputeVoting>,
     current_balance: i128,
     total: i128,
-) -> Result<(), ContractError> {
+) -> Result<(), Error> {
     if dispute_resolver != &escrow.roles.dispute_resolver {
-        return Err(ContractError::OnlyDisputeResolverCanExecuteThisFunction);
+        return Err(ContractError::OnlyDisputeResolverCanExecuteThisFunction.into());
     }
 
+    validate_escrow_resolution_terms(escrow, current_balance, total)?;
+    validate_lead_resolution_allowed(e, voting)
+}
+
+/// Checks that `total` is a valid settlement of an escrow-wide dispute.
+#[inline]
+pub fn validate_escrow_resolution_terms(
+    escrow: &Escrow,
+    current_balance: i128,
+    total: i128,
+) -> Result<(), Error> {
+    validate_escrow_not_cancelled(escrow)?;
+
     if !escrow.flags.disputed {
-        return Err(ContractError::EscrowNotInDispute);
+        return Err(ContractError::EscrowNotInDispute.into());
     }
 
     if current_balance < total {
-        return Err(ContractError::InsufficientFundsForResolution);
+        return Err(ContractError::InsufficientFundsForResolution.into());
     }
 
     if total != current_balance {
-        return Err(ContractError::DistributionsMustEqualEscrowBalance);
+        return Err(ContractError::DistributionsMustEqualEscrowBalance.into());
     }
 
     if total <= 0 {
-        return Err(ContractError::TotalAmountCannotBeZero);
+        return Err(ContractError::TotalAmountCannotBeZero.into());
     }
 
     Ok(())
@@ -39,11 +58,247 @@ pub fn validate_dispute_resolution_conditions(
 pub fn validate_dispute_flag_change_conditions(
     escrow: &Escrow,
     signer: &Address,
-) -> Result<(), ContractError> {
+    reason: &String,
+) -> Result<(), Error> {
+    validate_escrow_not_cancelled(escrow)?;
+
+    if reason.is_empty() {
+        return Err(DisputeError::EmptyDisputeReason.into());
+    }
+
+    if escrow.flags.released {
+        return Err(ContractError::EscrowAlreadyReleased.into());
+    }
+
+    if escrow.flags.resolved {
+        return Err(ContractError::EscrowAlreadyResolved.into());
+    }
+
+    if escrow.flags.disputed {
+        return Err(ContractError::EscrowAlreadyInDispute.into());
+    }
+
+    if escrow.milestones.iter().any(|milestone| milestone.disputed) {
+        return Err(DisputeError::MilestoneOpenedForDisputeResolution.into());
+    }
+
+    validate_dispute_signer(escrow, signer)
+}
+
+#[inline]
+pub fn validate_milestone_dispute_conditions(
+    escrow: &Escrow,
+    milestone: &Milestone,
+    signer: &Address,
+) -> Result<(), Error> {
+    validate_escrow_not_cancelled(escrow)?;
+
+    if escrow.flags.released {
+        return Err(ContractError::EscrowAlreadyReleased.into());
+    }
+
+    if escrow.flags.resolved {
+        return Err(ContractError::EscrowAlreadyResolved.into());
+    }
+
     if escrow.flags.disputed {
-        return Err(ContractError::EscrowAlreadyInDispute);
+        return Err(ContractError::EscrowAlreadyInDispute.into());
+    }
+
+    if milestone.disputed {
+        return Err(DisputeError::MilestoneAlreadyInDispute.into());
+    }
+
+    if milestone.resolved {
+        return Err(DisputeError::MilestoneAlreadyResolved.into());
+    }
+
+    if milestone.released {
+        return Err(ContractError::MilestoneAlreadyReleased.into());
+    }
+
+    validate_dispute_signer(escrow, signer)
+}
+
+#[inline]
+pub fn validate_milestone_dispute_resolution_conditions(
+    e: &Env,
+    escrow: &Escrow,
+    milestone: &Milestone,
+    dispute_resolver: &Address,
+    voting: &Option<DisputeVoting>,
+    current_balance: i128,
+    total: i128,
+) -> Result<(), Error> {
+    if dispute_resolver != &escrow.roles.dispute_resolver {
+        return Err(ContractError::OnlyDisputeResolverCanExecuteThisFunction.into());
+    }
+
+    validate_milestone_resolution_terms(escrow, milestone, current_balance, total)?;
+    validate_lead_resolution_allowed(e, voting)
+}
+
+/// Checks that `total` is a valid settlement of a dispute over `milestone`.
+#[inline]
+pub fn validate_milestone_resolution_terms(
+    escrow: &Escrow,
+    milestone: &Milestone,
+    current_balance: i128,
+    total: i128,
+) -> Result<(), Error> {
+    validate_escrow_not_cancelled(escrow)?;
+
+    if escrow.flags.resolved {
+        return Err(ContractError::EscrowAlreadyResolved.into());
+    }
+
+    if !milestone.disputed {
+        return Err(DisputeError::MilestoneNotInDispute.into());
+    }
+
+    if total <= 0 {
+        return Err(ContractError::TotalAmountCannotBeZero.into());
+    }
+
+    if total != milestone.amount {
+        return Err(DisputeError::DistributionsMustEqualMilestoneAmount.into());
+    }
+
+    if current_balance < total {
+        return Err(ContractError::InsufficientFundsForResolution.into());
+    }
+
+    Ok(())
+}
+
+/// The lead resolver can only settle a panel dispute once the panel's voting period is over.
+#[inline]
+fn validate_lead_resolution_allowed(e: &Env, voting: &Option<DisputeVoting>) -> Result<(), Error> {
+    if let Some(voting) = voting {
+        if e.ledger().timestamp() <= voting.deadline {
+            return Err(DisputeError::DisputeVotingOpen.into());
+        }
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_dispute_vote_conditions(
+    e: &Env,
+    escrow: &Escrow,
+    resolver: &Address,
+    voting: &DisputeVoting,
+) -> Result<(), Error> {
+    let is_panel_resolver = match &escrow.arbitration {
+        Arbitration::None => false,
+        Arbitration::Panel(panel) => panel.resolvers.contains(resolver),
+    };
+    if !is_panel_resolver {
+        return Err(DisputeError::OnlyArbitratorCanVote.into());
+    }
+
+    if e.ledger().timestamp() > voting.deadline {
+        return Err(DisputeError::DisputeVotingClosed.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_dispute_statement_conditions(
+    escrow: &Escrow,
+    signer: &Address,
+) -> Result<(), Error> {
+    if !is_escrow_role(&escrow.roles, signer) {
+        return Err(DisputeError::UnauthorizedToSubmitDisputeStatement.into());
+    }
+
+    if !escrow.flags.disputed {
+        return Err(ContractError::EscrowNotInDispute.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_appeal_conditions(
+    e: &Env,
+    escrow: &Escrow,
+    pending: &PendingResolution,
+    signer: &Address,
+) -> Result<(), Error> {
+    if signer != &escrow.roles.approver && signer != &escrow.roles.service_provider {
+        return Err(DisputeError::UnauthorizedToAppeal.into());
+    }
+
+    validate_escrow_not_cancelled(escrow)?;
+
+    if pending.appealed {
+        return Err(DisputeError::ResolutionAlreadyAppealed.into());
     }
 
+    if e.ledger().sequence() > pending.appealable_until {
+        return Err(DisputeError::AppealWindowClosed.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_appeal_resolution_conditions(
+    escrow: &Escrow,
+    pending: &PendingResolution,
+    appeal_resolver: &Address,
+) -> Result<(), Error> {
+    let is_appeal_resolver = match &escrow.appeal {
+        Appeal::None => false,
+        Appeal::Window(window) => &window.appeal_resolver == appeal_resolver,
+    };
+    if !is_appeal_resolver {
+        return Err(DisputeError::OnlyAppealResolverCanExecuteThisFunction.into());
+    }
+
+    if !pending.appealed {
+        return Err(DisputeError::ResolutionNotAppealed.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_no_pending_resolution(pending: &Option<PendingResolution>) -> Result<(), Error> {
+    if pending.is_some() {
+        return Err(DisputeError::ResolutionPending.into());
+    }
+
+    Ok(())
+}
+
+/// An appealed resolution is settled by the appeal resolver instead.
+#[inline]
+pub fn validate_resolution_finalization_conditions(
+    e: &Env,
+    pending: &PendingResolution,
+    current_balance: i128,
+) -> Result<(), Error> {
+    if pending.appealed {
+        return Err(DisputeError::ResolutionAlreadyAppealed.into());
+    }
+
+    if e.ledger().sequence() <= pending.appealable_until {
+        return Err(DisputeError::AppealWindowOpen.into());
+    }
+
+    if current_balance < pending.total {
+        return Err(ContractError::InsufficientFundsForResolution.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+fn is_escrow_role(roles: &Roles, signer: &Address) -> bool {
     let Roles {
         approver,
         service_provider,
@@ -51,21 +306,24 @@ pub fn validate_dispute_flag_change_conditions(
         release_signer,
         dispute_resolver,
         receiver,
-    } = &escrow.roles;
+    } = roles;
 
-    let is_authorized = signer == approver
+    signer == approver
         || signer == service_provider
         || signer == platform_address
         || signer == release_signer
         || signer == dispute_resolver
-        || signer == receiver;
+        || signer == receiver
+}
 
-    if !is_authorized {
-        return Err(ContractError::UnauthorizedToChangeDisputeFlag);
+#[inline]
+fn validate_dispute_signer(escrow: &Escrow, signer: &Address) -> Result<(), Error> {
+    if !is_escrow_role(&escrow.roles, signer) {
+        return Err(ContractError::UnauthorizedToChangeDisputeFlag.into());
     }
 
-    if signer == dispute_resolver {
-        return Err(ContractError::DisputeResolverCannotDisputeTheEscrow);
+    if signer == &escrow.roles.dispute_resolver {
+        return Err(ContractError::DisputeResolverCannotDisputeTheEscrow.into());
     }
 
     Ok(())
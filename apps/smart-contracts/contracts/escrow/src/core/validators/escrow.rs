@@ -1,37 +1,282 @@
-use soroban_sdk::{Address, Env};
+use soroban_sdk::{Address, Env, Error};
 
 use crate::{
-    error::ContractError,
-    storage::types::{DataKey, Escrow},
+    error::{ContractError, DisputeError, FundsError, WorkflowError},
+    modules::math::{BasicArithmetic, BasicMath},
+    storage::{
+        escrow::EscrowStorage,
+        types::{
+            Appeal, Arbitration, Deadline, Escrow, EscrowUpdateProposal, Milestone,
+            MilestoneStatus, Role,
+        },
+    },
 };
 
 #[inline]
-pub fn validate_release_conditions(
+fn is_deadline_reached(e: &Env, deadline: &Deadline) -> bool {
+    match deadline {
+        Deadline::None => false,
+        Deadline::Ledger(sequence) => e.ledger().sequence() >= *sequence,
+        Deadline::Timestamp(timestamp) => e.ledger().timestamp() >= *timestamp,
+    }
+}
+
+#[inline]
+fn validate_deadline(e: &Env, escrow: &Escrow) -> Result<(), Error> {
+    if is_deadline_reached(e, &escrow.deadline) {
+        return Err(FundsError::InvalidEscrowDeadline.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_escrow_not_cancelled(escrow: &Escrow) -> Result<(), Error> {
+    if escrow.flags.cancelled {
+        return Err(ContractError::EscrowCancelled.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_release_conditions(escrow: &Escrow, release_signer: &Address) -> Result<(), Error> {
+    validate_escrow_not_cancelled(escrow)?;
+
+    if escrow.flags.released {
+        return Err(ContractError::EscrowAlreadyReleased.into());
+    }
+
+    if escrow.flags.resolved {
+        return Err(ContractError::EscrowAlreadyResolved.into());
+    }
+
+    if release_signer != &escrow.roles.release_signer {
+        return Err(ContractError::OnlyReleaseSignerCanReleaseEarnings.into());
+    }
+
+    if escrow.milestones.is_empty() {
+        return Err(ContractError::NoMilestoneDefined.into());
+    }
+
+    if escrow.milestones.iter().any(|milestone| milestone.disputed) {
+        return Err(DisputeError::MilestoneOpenedForDisputeResolution.into());
+    }
+
+    if !escrow
+        .milestones
+        .iter()
+        .all(|milestone| milestone.approved || milestone.resolved)
+    {
+        return Err(ContractError::EscrowNotCompleted.into());
+    }
+
+    if escrow.flags.disputed {
+        return Err(ContractError::EscrowOpenedForDisputeResolution.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_milestone_release_conditions(
     escrow: &Escrow,
+    milestone: &Milestone,
     release_signer: &Address,
-) -> Result<(), ContractError> {
+) -> Result<(), Error> {
+    validate_escrow_not_cancelled(escrow)?;
+
     if escrow.flags.released {
-        return Err(ContractError::EscrowAlreadyReleased);
+        return Err(ContractError::EscrowAlreadyReleased.into());
     }
 
     if escrow.flags.resolved {
-        return Err(ContractError::EscrowAlreadyResolved);
+        return Err(ContractError::EscrowAlreadyResolved.into());
     }
 
     if release_signer != &escrow.roles.release_signer {
-        return Err(ContractError::OnlyReleaseSignerCanReleaseEarnings);
+        return Err(ContractError::OnlyReleaseSignerCanReleaseEarnings.into());
     }
 
-    if escrow.milestones.is_empty() {
-        return Err(ContractError::NoMilestoneDefined);
+    if milestone.disputed {
+        return Err(DisputeError::MilestoneOpenedForDisputeResolution.into());
+    }
+
+    if milestone.resolved {
+        return Err(DisputeError::MilestoneAlreadyResolved.into());
     }
 
-    if !escrow.milestones.iter().all(|milestone| milestone.approved) {
-        return Err(ContractError::EscrowNotCompleted);
+    if !milestone.approved {
+        return Err(ContractError::MilestoneNotApproved.into());
+    }
+
+    if milestone.released {
+        return Err(ContractError::MilestoneAlreadyReleased.into());
     }
 
     if escrow.flags.disputed {
-        return Err(ContractError::EscrowOpenedForDisputeResolution);
+        return Err(ContractError::EscrowOpenedForDisputeResolution.into());
+    }
+
+    Ok(())
+}
+
+/// Lifecycle, approval, rejection and dispute state is only ever set by the contract itself,
+/// so a milestone supplied by the platform must start `Pending` with none of it.
+#[inline]
+fn is_milestone_fresh(milestone: &Milestone) -> bool {
+    milestone.status == MilestoneStatus::Pending
+        && !milestone.approved
+        && !milestone.released
+        && !milestone.disputed
+        && !milestone.resolved
+        && milestone.rejection_reason.is_none()
+        && milestone.rejection_count == 0
+}
+
+/// An empty payee list means everything goes to the receiver.
+#[inline]
+fn validate_payees(escrow: &Escrow) -> Result<(), Error> {
+    if escrow.payees.is_empty() {
+        return Ok(());
+    }
+
+    if escrow.payees.len() > 10 {
+        return Err(FundsError::TooManyPayees.into());
+    }
+
+    let mut total_bps: u32 = 0;
+    for (index, payee) in escrow.payees.iter().enumerate() {
+        if payee.share_bps == 0 {
+            return Err(FundsError::PayeeSharesMustSumToTotal.into());
+        }
+        total_bps = total_bps.saturating_add(payee.share_bps);
+
+        if escrow
+            .payees
+            .iter()
+            .skip(index + 1)
+            .any(|other| other.address == payee.address)
+        {
+            return Err(FundsError::DuplicatePayee.into());
+        }
+    }
+
+    if total_bps != 10_000 {
+        return Err(FundsError::PayeeSharesMustSumToTotal.into());
+    }
+
+    Ok(())
+}
+
+fn validate_approvers(escrow: &Escrow) -> Result<(), Error> {
+    if escrow.co_approvers.len() > 10 {
+        return Err(WorkflowError::TooManyApprovers.into());
+    }
+
+    for (index, approver) in escrow.co_approvers.iter().enumerate() {
+        if approver == escrow.roles.approver
+            || escrow
+                .co_approvers
+                .iter()
+                .skip(index + 1)
+                .any(|other| other == approver)
+        {
+            return Err(WorkflowError::DuplicateApprover.into());
+        }
+    }
+
+    let approver_count = escrow.co_approvers.len() + 1;
+    if escrow.approval_threshold == 0 || escrow.approval_threshold > approver_count {
+        return Err(WorkflowError::InvalidApprovalThreshold.into());
+    }
+
+    Ok(())
+}
+
+fn validate_arbitration(escrow: &Escrow) -> Result<(), Error> {
+    let panel = match &escrow.arbitration {
+        Arbitration::None => return Ok(()),
+        Arbitration::Panel(panel) => panel,
+    };
+
+    if panel.resolvers.len() > 10 {
+        return Err(DisputeError::TooManyArbitrators.into());
+    }
+
+    for (index, resolver) in panel.resolvers.iter().enumerate() {
+        if panel
+            .resolvers
+            .iter()
+            .skip(index + 1)
+            .any(|other| other == resolver)
+        {
+            return Err(DisputeError::DuplicateArbitrator.into());
+        }
+    }
+
+    if panel.quorum == 0 || panel.quorum > panel.resolvers.len() {
+        return Err(DisputeError::InvalidDisputeQuorum.into());
+    }
+
+    if panel.voting_period == 0 {
+        return Err(DisputeError::InvalidVotingPeriod.into());
+    }
+
+    Ok(())
+}
+
+fn validate_appeal(escrow: &Escrow) -> Result<(), Error> {
+    if let Appeal::Window(window) = &escrow.appeal {
+        if window.ledgers == 0 {
+            return Err(DisputeError::InvalidAppealPeriod.into());
+        }
+
+        if window.appeal_resolver == escrow.roles.dispute_resolver {
+            return Err(DisputeError::AppealResolverCannotBeDisputeResolver.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Milestones may only depend on other milestones of the escrow, and the dependencies must
+/// leave an order in which every milestone can be completed. Escrows hold at most 50
+/// milestones, so the ones already ordered fit in a `u64` bit set.
+fn validate_milestone_dependencies(escrow: &Escrow) -> Result<(), Error> {
+    let milestone_count = escrow.milestones.len();
+    for (index, milestone) in escrow.milestones.iter().enumerate() {
+        if milestone
+            .depends_on
+            .iter()
+            .any(|dependency| dependency >= milestone_count || dependency == index as u32)
+        {
+            return Err(WorkflowError::InvalidMilestoneDependency.into());
+        }
+    }
+
+    let mut ordered: u64 = 0;
+    loop {
+        let mut progressed = false;
+        for (index, milestone) in escrow.milestones.iter().enumerate() {
+            let bit = 1u64 << index;
+            if ordered & bit == 0
+                && milestone
+                    .depends_on
+                    .iter()
+                    .all(|dependency| ordered & (1u64 << dependency) != 0)
+            {
+                ordered |= bit;
+                progressed = true;
+            }
+        }
+        if !progressed {
+            break;
+        }
+    }
+
+    if ordered.count_ones() != milestone_count {
+        return Err(WorkflowError::CyclicMilestoneDependencies.into());
     }
 
     Ok(())
@@ -43,58 +288,87 @@ pub fn validate_escrow_conditions(
     new_escrow: &Escrow,
     platform_address: Option<&Address>,
     contract_balance: Option<i128>,
+    protocol_fee_bps: u32,
     is_init: bool,
-) -> Result<(), ContractError> {
+) -> Result<(), Error> {
     let max_bps_percentage: u32 = 99 * 100;
     if new_escrow.platform_fee > max_bps_percentage {
-        return Err(ContractError::PlatformFeeTooHigh);
+        return Err(ContractError::PlatformFeeTooHigh.into());
     }
 
-    const TRUSTLESS_WORK_FEE_BPS: u32 = 30;
-    if (new_escrow.platform_fee as u32) + TRUSTLESS_WORK_FEE_BPS > 10_000 {
-        return Err(ContractError::PlatformFeeTooHigh);
+    if new_escrow.platform_fee + protocol_fee_bps > 10_000 {
+        return Err(ContractError::PlatformFeeTooHigh.into());
     }
 
     if new_escrow.amount < 0 {
-        return Err(ContractError::AmountCannotBeZero);
+        return Err(ContractError::AmountCannotBeNegative.into());
     }
 
     if new_escrow.milestones.is_empty() {
-        return Err(ContractError::NoMilestoneDefined);
+        return Err(ContractError::NoMilestoneDefined.into());
     }
     if new_escrow.milestones.len() > 50 {
-        return Err(ContractError::TooManyMilestones);
+        return Err(ContractError::TooManyMilestones.into());
+    }
+
+    let mut milestones_total: i128 = 0;
+    for milestone in new_escrow.milestones.iter() {
+        if milestone.amount < 0 {
+            return Err(ContractError::AmountCannotBeNegative.into());
+        }
+        if milestone.amount == 0 {
+            return Err(ContractError::AmountCannotBeZero.into());
+        }
+        milestones_total = BasicMath::safe_add(milestones_total, milestone.amount)?;
+    }
+    if milestones_total != new_escrow.amount {
+        return Err(ContractError::MilestoneAmountsMustEqualEscrowAmount.into());
+    }
+
+    validate_milestone_dependencies(new_escrow)?;
+    validate_payees(new_escrow)?;
+    validate_approvers(new_escrow)?;
+    validate_arbitration(new_escrow)?;
+    validate_appeal(new_escrow)?;
+
+    if new_escrow.review_period == Some(0) {
+        return Err(WorkflowError::InvalidReviewPeriod.into());
     }
 
     if is_init {
         if new_escrow.flags.released
             || new_escrow.flags.disputed
             || new_escrow.flags.resolved
-            || new_escrow.milestones.iter().any(|m| m.approved)
+            || new_escrow.flags.cancelled
+            || !new_escrow.milestones.iter().all(|m| is_milestone_fresh(&m))
         {
-            return Err(ContractError::FlagsMustBeFalse);
+            return Err(ContractError::FlagsMustBeFalse.into());
         }
     } else {
         let existing = existing_escrow.ok_or(ContractError::EscrowNotFound)?;
 
-        let caller = platform_address.ok_or(ContractError::OnlyPlatformAddressExecuteThisFunction)?;
+        let caller =
+            platform_address.ok_or(ContractError::OnlyPlatformAddressExecuteThisFunction)?;
         if caller != &existing.roles.platform_address {
-            return Err(ContractError::OnlyPlatformAddressExecuteThisFunction);
+            return Err(ContractError::OnlyPlatformAddressExecuteThisFunction.into());
         }
 
         if existing.roles.platform_address != new_escrow.roles.platform_address {
-            return Err(ContractError::PlatformAddressCannotBeChanged);
+            return Err(ContractError::PlatformAddressCannotBeChanged.into());
         }
 
+        validate_escrow_not_cancelled(existing)?;
+
         if existing.flags.disputed {
-            return Err(ContractError::EscrowOpenedForDisputeResolution);
+            return Err(ContractError::EscrowOpenedForDisputeResolution.into());
         }
 
         if new_escrow.flags.released
             || new_escrow.flags.disputed
             || new_escrow.flags.resolved
+            || new_escrow.flags.cancelled
         {
-            return Err(ContractError::FlagsMustBeFalse);
+            return Err(ContractError::FlagsMustBeFalse.into());
         }
 
         let has_funds = contract_balance.unwrap_or(0) > 0;
@@ -103,38 +377,51 @@ pub fn validate_escrow_conditions(
                 || existing.title != new_escrow.title
                 || existing.description != new_escrow.description
                 || existing.roles != new_escrow.roles
-                || existing.amount != new_escrow.amount
                 || existing.platform_fee != new_escrow.platform_fee
                 || existing.flags != new_escrow.flags
                 || existing.trustline != new_escrow.trustline
                 || existing.receiver_memo != new_escrow.receiver_memo
+                || existing.deadline != new_escrow.deadline
+                || existing.payees != new_escrow.payees
+                || existing.co_approvers != new_escrow.co_approvers
+                || existing.approval_threshold != new_escrow.approval_threshold
+                || existing.arbitration != new_escrow.arbitration
+                || existing.appeal != new_escrow.appeal
+                || existing.review_period != new_escrow.review_period
             {
-                return Err(ContractError::EscrowPropertiesMismatch);
+                return Err(ContractError::EscrowPropertiesMismatch.into());
             }
 
             let old_len = existing.milestones.len();
             let new_len = new_escrow.milestones.len();
             if new_len < old_len {
-                return Err(ContractError::EscrowPropertiesMismatch);
+                return Err(ContractError::EscrowPropertiesMismatch.into());
             }
             for i in 0..old_len {
                 if existing.milestones.get(i).unwrap() != new_escrow.milestones.get(i).unwrap() {
-                    return Err(ContractError::EscrowPropertiesMismatch);
+                    return Err(ContractError::EscrowPropertiesMismatch.into());
                 }
             }
 
             for i in old_len..new_len {
-                if new_escrow.milestones.get(i).unwrap().approved {
-                    return Err(ContractError::FlagsMustBeFalse);
+                if !is_milestone_fresh(&new_escrow.milestones.get(i).unwrap()) {
+                    return Err(ContractError::FlagsMustBeFalse.into());
                 }
             }
         } else {
             if existing.milestones.iter().any(|m| m.approved) {
-                return Err(ContractError::MilestoneApprovedCantChangeEscrowProperties);
+                return Err(ContractError::MilestoneApprovedCantChangeEscrowProperties.into());
             }
 
-            if new_escrow.milestones.iter().any(|m| m.approved) {
-                return Err(ContractError::FlagsMustBeFalse);
+            if existing.milestones.iter().any(|m| m.disputed) {
+                return Err(DisputeError::MilestoneOpenedForDisputeResolution.into());
+            }
+
+            for (i, milestone) in new_escrow.milestones.iter().enumerate() {
+                let unchanged = existing.milestones.get(i as u32).as_ref() == Some(&milestone);
+                if !unchanged && !is_milestone_fresh(&milestone) {
+                    return Err(ContractError::FlagsMustBeFalse.into());
+                }
             }
         }
     }
@@ -144,43 +431,244 @@ pub fn validate_escrow_conditions(
 
 #[inline]
 pub fn validate_escrow_property_change_conditions(
+    e: &Env,
     existing_escrow: &Escrow,
     new_escrow: &Escrow,
     platform_address: &Address,
     contract_balance: i128,
-) -> Result<(), ContractError> {
+    protocol_fee_bps: u32,
+) -> Result<(), Error> {
     validate_escrow_conditions(
         Some(existing_escrow),
         new_escrow,
         Some(platform_address),
         Some(contract_balance),
+        protocol_fee_bps,
         false,
-    )
+    )?;
+
+    if existing_escrow.deadline != new_escrow.deadline {
+        validate_deadline(e, new_escrow)?;
+    }
+
+    Ok(())
+}
+
+/// The platform may only update the escrow on its own while it holds no funds and the
+/// approver and service provider stay the same. Anything else goes through a proposal.
+#[inline]
+pub fn validate_direct_update_conditions(
+    existing_escrow: &Escrow,
+    new_escrow: &Escrow,
+    contract_balance: i128,
+) -> Result<(), Error> {
+    if contract_balance > 0
+        || existing_escrow.roles.approver != new_escrow.roles.approver
+        || existing_escrow.roles.service_provider != new_escrow.roles.service_provider
+    {
+        return Err(WorkflowError::EscrowUpdateRequiresConsent.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_update_acceptance_conditions(
+    e: &Env,
+    escrow: &Escrow,
+    proposal: &EscrowUpdateProposal,
+    signer: &Address,
+) -> Result<(), Error> {
+    if e.ledger().timestamp() > proposal.expires_at {
+        return Err(WorkflowError::UpdateProposalExpired.into());
+    }
+
+    if signer != &escrow.roles.approver && signer != &escrow.roles.service_provider {
+        return Err(WorkflowError::UnauthorizedToAcceptUpdate.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_role_transfer_conditions(
+    escrow: &Escrow,
+    role: Role,
+    current_holder: &Address,
+    new_address: &Address,
+) -> Result<(), Error> {
+    validate_escrow_not_cancelled(escrow)?;
+
+    if role == Role::PlatformAddress {
+        return Err(ContractError::PlatformAddressCannotBeChanged.into());
+    }
+
+    if current_holder == new_address {
+        return Err(WorkflowError::RoleAlreadyAssigned.into());
+    }
+
+    Ok(())
+}
+
+/// Checks the escrow as a role transfer leaves it, since the approvers, the arbitration
+/// panel and the appeal window are only valid for some role holders.
+#[inline]
+pub fn validate_transferred_roles(escrow: &Escrow) -> Result<(), Error> {
+    validate_approvers(escrow)?;
+    validate_arbitration(escrow)?;
+    validate_appeal(escrow)
 }
 
 #[inline]
 pub fn validate_initialize_escrow_conditions(
     e: &Env,
     escrow_properties: Escrow,
-) -> Result<(), ContractError> {
-    if e.storage().instance().has(&DataKey::Escrow) {
-        return Err(ContractError::EscrowAlreadyInitialized);
+    protocol_fee_bps: u32,
+) -> Result<(), Error> {
+    if EscrowStorage::has(e) {
+        return Err(ContractError::EscrowAlreadyInitialized.into());
     }
-    validate_escrow_conditions(None, &escrow_properties, None, None, true)
+    validate_escrow_conditions(None, &escrow_properties, None, None, protocol_fee_bps, true)?;
+    validate_deadline(e, &escrow_properties)
 }
 
 #[inline]
 pub fn validate_fund_escrow_conditions(
+    e: &Env,
     amount: i128,
     stored_escrow: &Escrow,
     expected_escrow: &Escrow,
-) -> Result<(), ContractError> {
+    remaining_amount: i128,
+) -> Result<(), Error> {
     if amount <= 0 {
-        return Err(ContractError::AmountCannotBeZero);
+        return Err(ContractError::AmountCannotBeZero.into());
+    }
+
+    if amount > remaining_amount {
+        return Err(FundsError::DepositExceedsEscrowAmount.into());
+    }
+
+    validate_escrow_not_cancelled(stored_escrow)?;
+
+    if !stored_escrow.eq(expected_escrow) {
+        return Err(ContractError::EscrowPropertiesMismatch.into());
+    }
+
+    if is_deadline_reached(e, &stored_escrow.deadline) {
+        return Err(FundsError::EscrowDeadlinePassed.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_refund_conditions(
+    e: &Env,
+    escrow: &Escrow,
+    current_balance: i128,
+) -> Result<(), Error> {
+    validate_escrow_not_cancelled(escrow)?;
+
+    if escrow.deadline == Deadline::None {
+        return Err(FundsError::EscrowDeadlineNotSet.into());
+    }
+
+    if !is_deadline_reached(e, &escrow.deadline) {
+        return Err(FundsError::EscrowDeadlineNotReached.into());
+    }
+
+    if escrow.flags.released {
+        return Err(ContractError::EscrowAlreadyReleased.into());
+    }
+
+    if escrow.flags.resolved {
+        return Err(ContractError::EscrowAlreadyResolved.into());
+    }
+
+    if escrow.flags.disputed {
+        return Err(ContractError::EscrowOpenedForDisputeResolution.into());
+    }
+
+    if escrow.milestones.iter().any(|m| m.disputed) {
+        return Err(DisputeError::MilestoneOpenedForDisputeResolution.into());
+    }
+
+    if escrow.milestones.iter().any(|m| m.approved) {
+        return Err(FundsError::EscrowHasApprovedMilestones.into());
+    }
+
+    if current_balance <= 0 {
+        return Err(FundsError::EscrowBalanceIsZero.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_sweep_conditions(
+    escrow: &Escrow,
+    platform_address: &Address,
+    current_balance: i128,
+) -> Result<(), Error> {
+    if platform_address != &escrow.roles.platform_address {
+        return Err(ContractError::OnlyPlatformAddressExecuteThisFunction.into());
+    }
+
+    if !escrow.flags.released && !escrow.flags.resolved && !escrow.flags.cancelled {
+        return Err(FundsError::EscrowNotFinished.into());
+    }
+
+    if current_balance <= 0 {
+        return Err(FundsError::EscrowBalanceIsZero.into());
+    }
+
+    // Each payee's share is rounded down, so a payout leaves at most one unit per payee
+    let max_dust = escrow.payees.len().max(1) as i128;
+    if current_balance > max_dust {
+        return Err(FundsError::BalanceExceedsDust.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_cancellation_conditions(
+    escrow: &Escrow,
+    platform_address: &Address,
+    approver: &Address,
+) -> Result<(), Error> {
+    if platform_address != &escrow.roles.platform_address {
+        return Err(ContractError::OnlyPlatformAddressExecuteThisFunction.into());
+    }
+
+    if approver != &escrow.roles.approver {
+        return Err(FundsError::UnauthorizedToCancelEscrow.into());
+    }
+
+    validate_escrow_not_cancelled(escrow)?;
+
+    if escrow.flags.released {
+        return Err(ContractError::EscrowAlreadyReleased.into());
+    }
+
+    if escrow.flags.resolved {
+        return Err(ContractError::EscrowAlreadyResolved.into());
+    }
+
+    if escrow.flags.disputed {
+        return Err(ContractError::EscrowOpenedForDisputeResolution.into());
+    }
+
+    if escrow.milestones.iter().any(|m| m.disputed) {
+        return Err(DisputeError::MilestoneOpenedForDisputeResolution.into());
     }
 
-    if !stored_escrow.eq(&expected_escrow) {
-        return Err(ContractError::EscrowPropertiesMismatch);
+    if escrow
+        .milestones
+        .iter()
+        .any(|m| m.approved || m.released || m.resolved)
+    {
+        return Err(FundsError::EscrowHasApprovedMilestones.into());
     }
 
     Ok(())
@@ -1,46 +1,244 @@
-use soroban_sdk::Address;
+use soroban_sdk::{Address, Error, String, Vec};
 
 use crate::{
-    error::ContractError,
-    storage::types::{Escrow, Milestone},
+    error::{ContractError, DisputeError, WorkflowError},
+    storage::types::{Escrow, Milestone, MilestoneStatus},
 };
 
+use super::escrow::validate_escrow_not_cancelled;
+
+/// Milestone indexes reach the entrypoints as `i128`. Returns the index as `u32` once it
+/// points at one of the escrow's milestones.
+#[inline]
+pub fn validate_milestone_index(escrow: &Escrow, milestone_index: i128) -> Result<u32, Error> {
+    if milestone_index < 0 || milestone_index >= escrow.milestones.len() as i128 {
+        return Err(ContractError::InvalidMileStoneIndex.into());
+    }
+
+    Ok(milestone_index as u32)
+}
+
 #[inline]
 pub fn validate_milestone_status_change_conditions(
     escrow: &Escrow,
+    milestone: &Milestone,
+    new_status: &MilestoneStatus,
     service_provider: &Address,
-) -> Result<(), ContractError> {
+) -> Result<(), Error> {
     if service_provider != &escrow.roles.service_provider {
-        return Err(ContractError::OnlyServiceProviderChangeMilstoneStatus);
+        return Err(ContractError::OnlyServiceProviderChangeMilstoneStatus.into());
     }
 
+    validate_escrow_not_cancelled(escrow)?;
+
     if escrow.milestones.is_empty() {
-        return Err(ContractError::NoMilestoneDefined);
+        return Err(ContractError::NoMilestoneDefined.into());
+    }
+
+    validate_milestone_not_in_dispute(milestone)?;
+
+    validate_service_provider_status_transition(&milestone.status, new_status)?;
+
+    if new_status == &MilestoneStatus::Submitted {
+        validate_milestone_dependencies_approved(escrow, milestone)?;
     }
 
     Ok(())
 }
 
+/// A milestone settled by a dispute resolution counts as approved for the milestones that
+/// depend on it, since it will never be approved otherwise.
+#[inline]
+pub fn validate_milestone_dependencies_approved(
+    escrow: &Escrow,
+    milestone: &Milestone,
+) -> Result<(), Error> {
+    for dependency in milestone.depends_on.iter() {
+        let prerequisite = escrow
+            .milestones
+            .get(dependency)
+            .ok_or(WorkflowError::InvalidMilestoneDependency)?;
+        if !prerequisite.approved && !prerequisite.resolved {
+            return Err(WorkflowError::MilestoneDependenciesNotApproved.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// The service provider drives a milestone from `Pending` to `Submitted`, and back to work
+/// after a rejection. Approving and releasing are reserved to the approver and release
+/// signer through their own entrypoints.
+#[inline]
+pub fn validate_service_provider_status_transition(
+    current_status: &MilestoneStatus,
+    new_status: &MilestoneStatus,
+) -> Result<(), Error> {
+    use MilestoneStatus::*;
+
+    let is_allowed = matches!(
+        (current_status, new_status),
+        (Pending, InProgress)
+            | (Pending, Submitted)
+            | (InProgress, InProgress)
+            | (InProgress, Submitted)
+            | (Submitted, Submitted)
+            | (Rejected, InProgress)
+            | (Rejected, Submitted)
+    );
+
+    if !is_allowed {
+        return Err(ContractError::InvalidMilestoneStatusTransition.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn is_approver(escrow: &Escrow, address: &Address) -> bool {
+    address == &escrow.roles.approver || escrow.co_approvers.contains(address)
+}
+
 #[inline]
 pub fn validate_milestone_flag_change_conditions(
     escrow: &Escrow,
     milestone: &Milestone,
     approver: &Address,
-) -> Result<(), ContractError> {
-    if approver != &escrow.roles.approver {
-        return Err(ContractError::OnlyApproverChangeMilstoneFlag);
+) -> Result<(), Error> {
+    if !is_approver(escrow, approver) {
+        return Err(ContractError::OnlyApproverChangeMilstoneFlag.into());
     }
 
+    validate_escrow_not_cancelled(escrow)?;
+
     if milestone.approved {
-        return Err(ContractError::MilestoneHasAlreadyBeenApproved);
+        return Err(ContractError::MilestoneHasAlreadyBeenApproved.into());
     }
 
-    if milestone.status.is_empty() {
-        return Err(ContractError::EmptyMilestoneStatus);
+    validate_milestone_not_in_dispute(milestone)?;
+
+    if milestone.status != MilestoneStatus::Submitted {
+        return Err(ContractError::InvalidMilestoneStatusTransition.into());
     }
 
     if escrow.milestones.is_empty() {
-        return Err(ContractError::NoMilestoneDefined);
+        return Err(ContractError::NoMilestoneDefined.into());
+    }
+
+    Ok(())
+}
+
+/// A milestone can be auto-approved once its review period has passed since it was last
+/// submitted. A rejection or a dispute moves it out of `Submitted` review.
+#[inline]
+pub fn validate_auto_approval_conditions(
+    escrow: &Escrow,
+    milestone: &Milestone,
+    submitted_at: Option<u64>,
+    now: u64,
+) -> Result<(), Error> {
+    let review_period = escrow
+        .review_period
+        .ok_or(WorkflowError::AutoApprovalNotEnabled)?;
+
+    validate_escrow_not_cancelled(escrow)?;
+
+    if escrow.flags.disputed {
+        return Err(ContractError::EscrowOpenedForDisputeResolution.into());
+    }
+
+    if milestone.approved {
+        return Err(ContractError::MilestoneHasAlreadyBeenApproved.into());
+    }
+
+    validate_milestone_not_in_dispute(milestone)?;
+
+    let submitted_at = match (milestone.status, submitted_at) {
+        (MilestoneStatus::Submitted, Some(submitted_at)) => submitted_at,
+        _ => return Err(WorkflowError::MilestoneNotSubmitted.into()),
+    };
+
+    if now < submitted_at.saturating_add(review_period) {
+        return Err(WorkflowError::ReviewPeriodNotElapsed.into());
+    }
+
+    validate_milestone_dependencies_approved(escrow, milestone)
+}
+
+#[inline]
+pub fn validate_milestone_approval_conditions(
+    escrow: &Escrow,
+    milestone: &Milestone,
+    approver: &Address,
+) -> Result<(), Error> {
+    validate_milestone_flag_change_conditions(escrow, milestone, approver)?;
+    validate_milestone_dependencies_approved(escrow, milestone)
+}
+
+#[inline]
+pub fn validate_milestone_rejection_conditions(
+    escrow: &Escrow,
+    milestone: &Milestone,
+    approver: &Address,
+    reason: &String,
+) -> Result<(), Error> {
+    validate_milestone_flag_change_conditions(escrow, milestone, approver)?;
+
+    if reason.is_empty() {
+        return Err(ContractError::EmptyRejectionReason.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_approval_withdrawal_conditions(
+    escrow: &Escrow,
+    milestone: &Milestone,
+    approver: &Address,
+) -> Result<(), Error> {
+    if !is_approver(escrow, approver) {
+        return Err(ContractError::OnlyApproverChangeMilstoneFlag.into());
+    }
+
+    validate_escrow_not_cancelled(escrow)?;
+
+    if milestone.approved {
+        return Err(ContractError::MilestoneHasAlreadyBeenApproved.into());
+    }
+
+    validate_milestone_not_in_dispute(milestone)
+}
+
+/// A batch must name each milestone at most once, so every entry is validated against the
+/// milestone as it was before the batch.
+#[inline]
+pub fn validate_milestone_batch(milestone_indexes: &Vec<u32>) -> Result<(), Error> {
+    if milestone_indexes.is_empty() {
+        return Err(WorkflowError::EmptyMilestoneBatch.into());
+    }
+
+    for (position, milestone_index) in milestone_indexes.iter().enumerate() {
+        if milestone_indexes
+            .iter()
+            .skip(position + 1)
+            .any(|other| other == milestone_index)
+        {
+            return Err(WorkflowError::DuplicateMilestoneIndex.into());
+        }
+    }
+
+    Ok(())
+}
+
+#[inline]
+fn validate_milestone_not_in_dispute(milestone: &Milestone) -> Result<(), Error> {
+    if milestone.disputed {
+        return Err(DisputeError::MilestoneOpenedForDisputeResolution.into());
+    }
+
+    if milestone.resolved {
+        return Err(DisputeError::MilestoneAlreadyResolved.into());
     }
 
     Ok(())
@@ -0,0 +1,14 @@
+use soroban_sdk::Error;
+
+use crate::{
+    core::protocol::MAX_PROTOCOL_FEE_BPS, error::FundsError, storage::types::ProtocolConfig,
+};
+
+#[inline]
+pub fn validate_protocol_config_conditions(protocol_config: &ProtocolConfig) -> Result<(), Error> {
+    if protocol_config.fee_bps > MAX_PROTOCOL_FEE_BPS {
+        return Err(FundsError::InvalidProtocolFee.into());
+    }
+
+    Ok(())
+}
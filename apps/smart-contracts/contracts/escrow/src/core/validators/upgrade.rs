@@ -0,0 +1,31 @@
+use soroban_sdk::{Address, Error};
+
+use crate::error::ContractError;
+
+#[inline]
+pub fn validate_upgrade_conditions(
+    stored_platform: &Address,
+    platform_address: &Address,
+) -> Result<(), Error> {
+    if platform_address != stored_platform {
+        return Err(ContractError::OnlyPlatformAddressExecuteThisFunction.into());
+    }
+
+    Ok(())
+}
+
+#[inline]
+pub fn validate_migration_conditions(
+    stored_platform: &Address,
+    platform_address: &Address,
+    stored_version: u32,
+    contract_version: u32,
+) -> Result<(), Error> {
+    validate_upgrade_conditions(stored_platform, platform_address)?;
+
+    if stored_version >= contract_version {
+        return Err(ContractError::StorageAlreadyMigrated.into());
+    }
+
+    Ok(())
+}
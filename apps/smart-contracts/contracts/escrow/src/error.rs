@@ -1,6 +1,9 @@
 use core::fmt;
 use soroban_sdk::contracterror;
 
+// The contract spec caps an error enum at 50 cases, so the errors are split by subsystem.
+// Codes are unique across the enums, so each code keeps a single meaning for callers.
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[contracterror]
 pub enum ContractError {
@@ -35,7 +38,6 @@ pub enum ContractError {
     AmountsToBeTransferredShouldBePositive = 38,
     DistributionsMustEqualEscrowBalance = 39,
     MilestoneHasAlreadyBeenApproved = 29,
-    EmptyMilestoneStatus = 30,
     PlatformFeeTooHigh = 31,
     FlagsMustBeFalse = 32,
     EscrowPropertiesMismatch = 33,
@@ -45,6 +47,94 @@ pub enum ContractError {
     PlatformAddressCannotBeChanged = 37,
     DisputeResolverCannotDisputeTheEscrow = 40,
     TotalAmountCannotBeZero = 41,
+    MilestoneAmountsMustEqualEscrowAmount = 42,
+    MilestoneNotApproved = 43,
+    MilestoneAlreadyReleased = 44,
+    InvalidMilestoneStatusTransition = 51,
+    EmptyRejectionReason = 52,
+    StorageAlreadyMigrated = 58,
+    EscrowCancelled = 67,
+    AmountCannotBeNegative = 108,
+}
+
+/// Errors of deadlines and refunds, deposits, payees, protocol fees, sweeps and cancellation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[contracterror]
+pub enum FundsError {
+    EscrowDeadlineNotSet = 45,
+    EscrowDeadlineNotReached = 46,
+    EscrowDeadlinePassed = 47,
+    InvalidEscrowDeadline = 48,
+    EscrowHasApprovedMilestones = 49,
+    EscrowBalanceIsZero = 50,
+    OnlyAdminCanExecuteThisFunction = 61,
+    InvalidProtocolFee = 62,
+    PayeeSharesMustSumToTotal = 63,
+    DuplicatePayee = 64,
+    TooManyPayees = 65,
+    EscrowNotFinished = 66,
+    UnauthorizedToCancelEscrow = 109,
+    DepositExceedsEscrowAmount = 113,
+    BalanceExceedsDust = 114,
+}
+
+/// Errors of update proposals, role transfers, co-approvals, review periods, milestone
+/// dependencies, batches and the evidence log.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[contracterror]
+pub enum WorkflowError {
+    EscrowUpdateRequiresConsent = 68,
+    UpdateProposalNotFound = 69,
+    UpdateProposalExpired = 70,
+    UnauthorizedToAcceptUpdate = 71,
+    RoleAlreadyAssigned = 72,
+    InvalidApprovalThreshold = 73,
+    DuplicateApprover = 74,
+    MilestoneAlreadyApprovedBySigner = 75,
+    MilestoneApprovalNotFound = 76,
+    TooManyApprovers = 77,
+    InvalidReviewPeriod = 99,
+    AutoApprovalNotEnabled = 100,
+    MilestoneNotSubmitted = 101,
+    ReviewPeriodNotElapsed = 102,
+    InvalidMilestoneDependency = 103,
+    CyclicMilestoneDependencies = 104,
+    MilestoneDependenciesNotApproved = 105,
+    EmptyMilestoneBatch = 106,
+    DuplicateMilestoneIndex = 107,
+    TooManyEvidenceEntries = 112,
+}
+
+/// Errors of milestone disputes, arbitration panels, dispute records and appeals.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[contracterror]
+pub enum DisputeError {
+    MilestoneAlreadyInDispute = 53,
+    MilestoneNotInDispute = 54,
+    MilestoneAlreadyResolved = 55,
+    MilestoneOpenedForDisputeResolution = 56,
+    DistributionsMustEqualMilestoneAmount = 57,
+    InvalidDisputeQuorum = 78,
+    DuplicateArbitrator = 79,
+    TooManyArbitrators = 80,
+    InvalidVotingPeriod = 81,
+    OnlyArbitratorCanVote = 82,
+    DisputeVotingOpen = 83,
+    DisputeVotingClosed = 84,
+    DisputeVotingNotFound = 85,
+    EmptyDisputeReason = 86,
+    DisputeRecordNotFound = 87,
+    UnauthorizedToSubmitDisputeStatement = 88,
+    InvalidAppealPeriod = 89,
+    AppealResolverCannotBeDisputeResolver = 90,
+    ResolutionPending = 91,
+    PendingResolutionNotFound = 92,
+    AppealWindowClosed = 93,
+    AppealWindowOpen = 94,
+    ResolutionAlreadyAppealed = 95,
+    ResolutionNotAppealed = 96,
+    OnlyAppealResolverCanExecuteThisFunction = 97,
+    UnauthorizedToAppeal = 98,
 }
 
 impl fmt::Display for ContractError {
@@ -88,6 +178,9 @@ impl fmt::Display for ContractError {
             ContractError::EscrowOpenedForDisputeResolution => {
                 write!(f, "Escrow has been opened for dispute resolution")
             }
+            ContractError::Overflow => write!(f, "This operation can cause an Overflow"),
+            ContractError::Underflow => write!(f, "This operation can cause an Underflow"),
+            ContractError::DivisionError => write!(f, "This operation can cause Division error"),
             ContractError::InsufficientApproverFundsForCommissions => {
                 write!(f, "Insufficient approver funds for commissions")
             }
@@ -101,9 +194,6 @@ impl fmt::Display for ContractError {
                 )
             }
             ContractError::EscrowHasFunds => write!(f, "Escrow has funds"),
-            ContractError::Overflow => write!(f, "This operation can cause an Overflow"),
-            ContractError::Underflow => write!(f, "This operation can cause an Underflow"),
-            ContractError::DivisionError => write!(f, "This operation can cause Division error"),
             ContractError::EscrowAlreadyResolved => write!(f, "This escrow is already resolved"),
             ContractError::TooManyEscrowsRequested => {
                 write!(f, "You have requested too many escrows")
@@ -135,9 +225,6 @@ impl fmt::Display for ContractError {
                     "You cannot approve a milestone that has already been approved previously"
                 )
             }
-            ContractError::EmptyMilestoneStatus => {
-                write!(f, "The milestone status cannot be empty")
-            }
             ContractError::PlatformFeeTooHigh => {
                 write!(f, "The platform fee cannot exceed 99%")
             }
@@ -162,7 +249,7 @@ impl fmt::Display for ContractError {
             ContractError::IncompatibleEscrowWasmHash => {
                 write!(
                     f,
-                    "The provided contract address is not an instance of this escrow contract."
+                    "The installed escrow code is not compatible with the stored escrow state."
                 )
             }
             ContractError::PlatformAddressCannotBeChanged => {
@@ -174,6 +261,223 @@ impl fmt::Display for ContractError {
             ContractError::TotalAmountCannotBeZero => {
                 write!(f, "The total amount to be distributed cannot be equal to zero.")
             }
+            ContractError::MilestoneAmountsMustEqualEscrowAmount => {
+                write!(f, "The sum of the milestone amounts must equal the escrow amount.")
+            }
+            ContractError::MilestoneNotApproved => {
+                write!(f, "The milestone must be approved before its funds can be released.")
+            }
+            ContractError::MilestoneAlreadyReleased => {
+                write!(f, "The funds of this milestone have already been released.")
+            }
+            ContractError::InvalidMilestoneStatusTransition => {
+                write!(f, "The milestone cannot move from its current status to the requested one.")
+            }
+            ContractError::EmptyRejectionReason => {
+                write!(f, "A reason must be given when rejecting a milestone.")
+            }
+            ContractError::StorageAlreadyMigrated => {
+                write!(f, "The escrow storage has already been migrated.")
+            }
+            ContractError::EscrowCancelled => write!(f, "The escrow has been cancelled."),
+            ContractError::AmountCannotBeNegative => write!(f, "Amount cannot be negative"),
+        }
+    }
+}
+
+impl fmt::Display for FundsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FundsError::EscrowDeadlineNotSet => {
+                write!(f, "The escrow does not define a deadline.")
+            }
+            FundsError::EscrowDeadlineNotReached => {
+                write!(f, "The escrow deadline has not been reached yet.")
+            }
+            FundsError::EscrowDeadlinePassed => {
+                write!(f, "The escrow deadline has already passed.")
+            }
+            FundsError::InvalidEscrowDeadline => {
+                write!(f, "The escrow deadline must be in the future.")
+            }
+            FundsError::EscrowHasApprovedMilestones => {
+                write!(f, "The escrow cannot be refunded once a milestone has been approved.")
+            }
+            FundsError::EscrowBalanceIsZero => {
+                write!(f, "The escrow has no balance to refund.")
+            }
+            FundsError::OnlyAdminCanExecuteThisFunction => {
+                write!(f, "Only the admin can execute this function")
+            }
+            FundsError::InvalidProtocolFee => {
+                write!(f, "The protocol fee cannot exceed 100 basis points.")
+            }
+            FundsError::PayeeSharesMustSumToTotal => {
+                write!(f, "Payee shares must be positive and add up to 10000 basis points.")
+            }
+            FundsError::DuplicatePayee => write!(f, "A payee can only be listed once."),
+            FundsError::TooManyPayees => write!(f, "Cannot define more than 10 payees."),
+            FundsError::EscrowNotFinished => {
+                write!(f, "The escrow must be fully released or resolved first.")
+            }
+            FundsError::UnauthorizedToCancelEscrow => {
+                write!(f, "Only the platform together with the approver can cancel the escrow.")
+            }
+            FundsError::DepositExceedsEscrowAmount => {
+                write!(f, "The deposit would fund more than the unreleased escrow amount.")
+            }
+            FundsError::BalanceExceedsDust => {
+                write!(f, "Only rounding dust of at most one unit per payee can be swept.")
+            }
+        }
+    }
+}
+
+impl fmt::Display for WorkflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkflowError::EscrowUpdateRequiresConsent => write!(
+                f,
+                "This update must be proposed and accepted by the approver and service provider."
+            ),
+            WorkflowError::UpdateProposalNotFound => {
+                write!(f, "There is no pending escrow update proposal.")
+            }
+            WorkflowError::UpdateProposalExpired => {
+                write!(f, "The escrow update proposal has expired.")
+            }
+            WorkflowError::UnauthorizedToAcceptUpdate => {
+                write!(f, "Only the approver or the service provider can accept an escrow update.")
+            }
+            WorkflowError::RoleAlreadyAssigned => {
+                write!(f, "The role is already held by this address.")
+            }
+            WorkflowError::InvalidApprovalThreshold => write!(
+                f,
+                "The approval threshold must be between 1 and the number of approvers."
+            ),
+            WorkflowError::DuplicateApprover => write!(f, "An approver can only be listed once."),
+            WorkflowError::MilestoneAlreadyApprovedBySigner => {
+                write!(f, "This approver has already approved the milestone.")
+            }
+            WorkflowError::MilestoneApprovalNotFound => {
+                write!(f, "This approver has not approved the milestone.")
+            }
+            WorkflowError::TooManyApprovers => {
+                write!(f, "Cannot define more than 10 co-approvers.")
+            }
+            WorkflowError::InvalidReviewPeriod => {
+                write!(f, "The review period must be greater than zero.")
+            }
+            WorkflowError::AutoApprovalNotEnabled => {
+                write!(f, "Milestones of this escrow cannot be auto-approved.")
+            }
+            WorkflowError::MilestoneNotSubmitted => {
+                write!(f, "The milestone has not been submitted for review.")
+            }
+            WorkflowError::ReviewPeriodNotElapsed => {
+                write!(f, "The review period of the milestone has not elapsed yet.")
+            }
+            WorkflowError::InvalidMilestoneDependency => {
+                write!(f, "A milestone can only depend on other milestones of the escrow.")
+            }
+            WorkflowError::CyclicMilestoneDependencies => {
+                write!(f, "Milestone dependencies cannot form a cycle.")
+            }
+            WorkflowError::MilestoneDependenciesNotApproved => {
+                write!(f, "The milestones this one depends on have not been approved yet.")
+            }
+            WorkflowError::EmptyMilestoneBatch => {
+                write!(f, "At least one milestone must be given.")
+            }
+            WorkflowError::DuplicateMilestoneIndex => {
+                write!(f, "A milestone can only appear once in a batch.")
+            }
+            WorkflowError::TooManyEvidenceEntries => {
+                write!(f, "A milestone cannot hold more than 20 evidence entries.")
+            }
+        }
+    }
+}
+
+impl fmt::Display for DisputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisputeError::MilestoneAlreadyInDispute => write!(f, "Milestone already in dispute"),
+            DisputeError::MilestoneNotInDispute => write!(f, "Milestone not in dispute"),
+            DisputeError::MilestoneAlreadyResolved => {
+                write!(f, "The dispute over this milestone has already been resolved.")
+            }
+            DisputeError::MilestoneOpenedForDisputeResolution => {
+                write!(f, "Milestone has been opened for dispute resolution")
+            }
+            DisputeError::DistributionsMustEqualMilestoneAmount => {
+                write!(f, "The sum of distributions must equal the amount of the disputed milestone.")
+            }
+            DisputeError::InvalidDisputeQuorum => write!(
+                f,
+                "The dispute quorum must be between 1 and the number of panel resolvers."
+            ),
+            DisputeError::DuplicateArbitrator => {
+                write!(f, "A resolver can only be listed once in the arbitration panel.")
+            }
+            DisputeError::TooManyArbitrators => {
+                write!(f, "Cannot define more than 10 resolvers in the arbitration panel.")
+            }
+            DisputeError::InvalidVotingPeriod => {
+                write!(f, "The dispute voting period must be greater than zero.")
+            }
+            DisputeError::OnlyArbitratorCanVote => {
+                write!(f, "Only a resolver of the arbitration panel can vote on a dispute.")
+            }
+            DisputeError::DisputeVotingOpen => {
+                write!(f, "The arbitration panel is still voting on this dispute.")
+            }
+            DisputeError::DisputeVotingClosed => {
+                write!(f, "The voting period for this dispute has ended.")
+            }
+            DisputeError::DisputeVotingNotFound => {
+                write!(f, "There is no panel vote open for this dispute.")
+            }
+            DisputeError::EmptyDisputeReason => {
+                write!(f, "A reason must be given when disputing the escrow.")
+            }
+            DisputeError::DisputeRecordNotFound => {
+                write!(f, "The escrow has never been disputed.")
+            }
+            DisputeError::UnauthorizedToSubmitDisputeStatement => {
+                write!(f, "Only an escrow role can submit a dispute statement.")
+            }
+            DisputeError::InvalidAppealPeriod => {
+                write!(f, "The appeal window must last at least one ledger.")
+            }
+            DisputeError::AppealResolverCannotBeDisputeResolver => {
+                write!(f, "The appeal resolver must be different from the dispute resolver.")
+            }
+            DisputeError::ResolutionPending => {
+                write!(f, "A resolution has already been decided for this dispute.")
+            }
+            DisputeError::PendingResolutionNotFound => {
+                write!(f, "There is no pending resolution for this dispute.")
+            }
+            DisputeError::AppealWindowClosed => {
+                write!(f, "The appeal window for this resolution has closed.")
+            }
+            DisputeError::AppealWindowOpen => {
+                write!(f, "The resolution can still be appealed.")
+            }
+            DisputeError::ResolutionAlreadyAppealed => {
+                write!(f, "This resolution has already been appealed.")
+            }
+            DisputeError::ResolutionNotAppealed => {
+                write!(f, "This resolution has not been appealed.")
+            }
+            DisputeError::OnlyAppealResolverCanExecuteThisFunction => {
+                write!(f, "Only the appeal resolver can execute this function")
+            }
+            DisputeError::UnauthorizedToAppeal => {
+                write!(f, "Only the approver or the service provider can appeal a resolution.")
+            }
         }
     }
 }
@@ -1,57 +1,286 @@
-use crate::storage::types::Escrow;
+//! Events that describe a change to the escrow carry its engagement id as their last topic
+//! and only the fields that changed as data. Their second topic, `v1`, is the version of
+//! that layout and changes whenever one of them changes shape. The admin, TTL and upgrade
+//! events at the end concern the contract rather than the escrow and keep their own layout.
+
+use crate::storage::types::{
+    DisputeTarget, EscrowChanges, Evidence, MilestoneStatus, Payout, ProtocolConfig, Role,
+    TransferKind,
+};
 use soroban_sdk::{contractevent, String};
 
-#[contractevent(topics = ["tw_init"], data_format = "vec")]
+#[contractevent(topics = ["tw_init", "v1"], data_format = "vec")]
 #[derive(Clone)]
 pub struct InitEsc {
-    pub escrow: Escrow,
+    #[topic]
+    pub engagement_id: String,
+    pub amount: i128,
+    pub milestone_count: u32,
 }
 
-#[contractevent(topics = ["tw_fund"], data_format = "vec")]
+#[contractevent(topics = ["tw_fund", "v1"], data_format = "vec")]
 #[derive(Clone)]
 pub struct FundEsc {
+    #[topic]
+    pub engagement_id: String,
     pub signer: soroban_sdk::Address,
     pub amount: i128,
 }
 
-#[contractevent(topics = ["tw_release"], data_format = "single-value")]
+#[contractevent(topics = ["tw_release", "v1"], data_format = "single-value")]
 #[derive(Clone)]
 pub struct DisEsc {
+    #[topic]
+    pub engagement_id: String,
     pub release_signer: soroban_sdk::Address,
 }
 
-#[contractevent(topics = ["tw_update"], data_format = "vec")]
+/// One token transfer out of the escrow, published for every transfer a release or a
+/// dispute resolution makes.
+#[contractevent(topics = ["tw_transfer", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct FundsTransferred {
+    #[topic]
+    pub engagement_id: String,
+    pub kind: TransferKind,
+    pub recipient: soroban_sdk::Address,
+    pub amount: i128,
+}
+
+/// How a release or a dispute resolution was split: the two fees and, per recipient, its
+/// gross amount, the fees it bore and what it was paid, as `FeeCalculator` computed them.
+#[contractevent(topics = ["tw_payout", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct PayoutBreakdown {
+    #[topic]
+    pub engagement_id: String,
+    pub protocol_fee: i128,
+    pub platform_fee: i128,
+    pub payouts: soroban_sdk::Vec<Payout>,
+}
+
+#[contractevent(topics = ["tw_refund", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct EscrowRefunded {
+    #[topic]
+    pub engagement_id: String,
+    pub depositor: soroban_sdk::Address,
+    pub amount: i128,
+}
+
+/// Published when an update is applied, with what it changed.
+#[contractevent(topics = ["tw_update", "v1"], data_format = "vec")]
 #[derive(Clone)]
 pub struct ChgEsc {
+    #[topic]
+    pub engagement_id: String,
     pub platform: soroban_sdk::Address,
+    pub changes: EscrowChanges,
+}
+
+#[contractevent(topics = ["tw_update_prop", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct EscrowUpdateProposed {
+    #[topic]
     pub engagement_id: String,
-    pub new_escrow_properties: Escrow,
+    pub platform: soroban_sdk::Address,
+    pub changes: EscrowChanges,
+    pub expires_at: u64,
+}
+
+#[contractevent(topics = ["tw_update_accept", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct EscrowUpdateAccepted {
+    #[topic]
+    pub engagement_id: String,
+    pub signer: soroban_sdk::Address,
+}
+
+#[contractevent(topics = ["tw_role", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct RoleTransferred {
+    #[topic]
+    pub engagement_id: String,
+    pub role: Role,
+    pub previous_holder: soroban_sdk::Address,
+    pub new_holder: soroban_sdk::Address,
+}
+
+#[contractevent(topics = ["tw_cancel", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct EscrowCancelled {
+    #[topic]
+    pub engagement_id: String,
+    pub platform: soroban_sdk::Address,
+    pub approver: soroban_sdk::Address,
+}
+
+#[contractevent(topics = ["tw_sweep", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct DustSwept {
+    #[topic]
+    pub engagement_id: String,
+    pub platform: soroban_sdk::Address,
+    pub amount: i128,
 }
 
 // Milestones
-#[contractevent(topics = ["tw_ms_change"], data_format = "vec")]
+#[contractevent(topics = ["tw_ms_change", "v1"], data_format = "vec")]
 #[derive(Clone)]
 pub struct MilestoneStatusChanged {
-    pub escrow: Escrow,
+    #[topic]
+    pub engagement_id: String,
+    pub milestone_index: i128,
+    pub old_status: MilestoneStatus,
+    pub new_status: MilestoneStatus,
 }
 
-#[contractevent(topics = ["tw_ms_approve"], data_format = "vec")]
+#[contractevent(topics = ["tw_ms_evidence", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct EvidenceSubmitted {
+    #[topic]
+    pub engagement_id: String,
+    pub milestone_index: i128,
+    pub evidence: Evidence,
+}
+
+#[contractevent(topics = ["tw_ms_approve", "v1"], data_format = "vec")]
 #[derive(Clone)]
 pub struct MilestoneApproved {
-    pub escrow: Escrow,
+    #[topic]
+    pub engagement_id: String,
+    pub milestone_index: i128,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["tw_ms_approval", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct MilestoneApprovalAdded {
+    #[topic]
+    pub engagement_id: String,
+    pub approver: soroban_sdk::Address,
+    pub milestone_index: i128,
+    pub approvals: u32,
+    pub approval_threshold: u32,
+}
+
+/// Published instead of `MilestoneApproved` when the milestone was approved by
+/// `auto_approve` after its review period, rather than by its approvers.
+#[contractevent(topics = ["tw_ms_auto_approve", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct MilestoneAutoApproved {
+    #[topic]
+    pub engagement_id: String,
+    pub milestone_index: i128,
+    pub amount: i128,
+    pub submitted_at: u64,
+    pub approved_at: u64,
+}
+
+#[contractevent(topics = ["tw_ms_unapprove", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct MilestoneApprovalWithdrawn {
+    #[topic]
+    pub engagement_id: String,
+    pub approver: soroban_sdk::Address,
+    pub milestone_index: i128,
+    pub approvals: u32,
+}
+
+#[contractevent(topics = ["tw_ms_reject", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct MilestoneRejected {
+    #[topic]
+    pub engagement_id: String,
+    pub approver: soroban_sdk::Address,
+    pub milestone_index: i128,
+    pub reason: String,
+}
+
+#[contractevent(topics = ["tw_ms_release", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct MilestoneReleased {
+    #[topic]
+    pub engagement_id: String,
+    pub release_signer: soroban_sdk::Address,
+    pub milestone_index: i128,
+    pub amount: i128,
 }
 
 // Disputes
-#[contractevent(topics = ["tw_disp_resolve"], data_format = "vec")]
+#[contractevent(topics = ["tw_disp_resolve", "v1"], data_format = "vec")]
 #[derive(Clone)]
 pub struct DisputeResolved {
-    pub escrow: Escrow,
+    #[topic]
+    pub engagement_id: String,
+    pub resolved_by: soroban_sdk::Address,
+    pub distributions: soroban_sdk::Map<soroban_sdk::Address, i128>,
 }
 
-#[contractevent(topics = ["tw_dispute"], data_format = "vec")]
+#[contractevent(topics = ["tw_dispute", "v1"], data_format = "vec")]
 #[derive(Clone)]
 pub struct EscrowDisputed {
-    pub escrow: Escrow,
+    #[topic]
+    pub engagement_id: String,
+    pub signer: soroban_sdk::Address,
+    pub reason: String,
+}
+
+#[contractevent(topics = ["tw_dispute_stmt", "v1"], data_format = "single-value")]
+#[derive(Clone)]
+pub struct DisputeStatementSubmitted {
+    #[topic]
+    pub engagement_id: String,
+    pub statement: Evidence,
+}
+
+#[contractevent(topics = ["tw_ms_dispute", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct MilestoneDisputed {
+    #[topic]
+    pub engagement_id: String,
+    pub signer: soroban_sdk::Address,
+    pub milestone_index: i128,
+}
+
+#[contractevent(topics = ["tw_ms_disp_resolve", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct MilestoneDisputeResolved {
+    #[topic]
+    pub engagement_id: String,
+    pub dispute_resolver: soroban_sdk::Address,
+    pub milestone_index: i128,
+    pub distributions: soroban_sdk::Map<soroban_sdk::Address, i128>,
+}
+
+#[contractevent(topics = ["tw_dispute_vote", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct DisputeVoteCast {
+    #[topic]
+    pub engagement_id: String,
+    pub resolver: soroban_sdk::Address,
+    pub target: DisputeTarget,
+    pub distributions: soroban_sdk::Map<soroban_sdk::Address, i128>,
+}
+
+#[contractevent(topics = ["tw_res_posted", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct ResolutionPosted {
+    #[topic]
+    pub engagement_id: String,
+    pub target: DisputeTarget,
+    pub decided_by: soroban_sdk::Address,
+    pub distributions: soroban_sdk::Map<soroban_sdk::Address, i128>,
+    pub appealable_until: u32,
+}
+
+#[contractevent(topics = ["tw_appeal", "v1"], data_format = "vec")]
+#[derive(Clone)]
+pub struct ResolutionAppealed {
+    #[topic]
+    pub engagement_id: String,
+    pub target: DisputeTarget,
+    pub appellant: soroban_sdk::Address,
 }
 
 // Admin / TTL
@@ -61,3 +290,32 @@ pub struct ExtTtlEvt {
     pub platform: soroban_sdk::Address,
     pub ledgers_to_extend: u32,
 }
+
+#[contractevent(topics = ["tw_ms_ttl_extend"], data_format = "vec")]
+#[derive(Clone)]
+pub struct MilestoneTtlExtended {
+    pub platform: soroban_sdk::Address,
+    pub milestone_index: i128,
+    pub ledgers_to_extend: u32,
+}
+
+#[contractevent(topics = ["tw_protocol"], data_format = "vec")]
+#[derive(Clone)]
+pub struct ProtocolConfigUpdated {
+    pub admin: soroban_sdk::Address,
+    pub protocol_config: ProtocolConfig,
+}
+
+#[contractevent(topics = ["tw_upgrade"], data_format = "vec")]
+#[derive(Clone)]
+pub struct EscrowUpgraded {
+    pub platform: soroban_sdk::Address,
+    pub new_wasm_hash: soroban_sdk::BytesN<32>,
+}
+
+#[contractevent(topics = ["tw_version"], data_format = "vec")]
+#[derive(Clone)]
+pub struct EscrowMigrated {
+    pub from_version: u32,
+    pub to_version: u32,
+}
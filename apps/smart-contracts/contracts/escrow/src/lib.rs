@@ -2,16 +2,35 @@
 
 mod contract;
 mod core {
+    pub mod appeal;
+    pub mod approval;
+    pub mod arbitration;
+    pub mod deposit;
     pub mod dispute;
     pub mod escrow;
+    pub mod evidence;
     pub mod milestone;
+    pub mod payout;
+    pub mod proposal;
+    pub mod protocol;
+    pub mod review;
+    pub mod role;
+    pub mod upgrade;
+    pub use appeal::*;
+    pub use deposit::*;
     pub use dispute::*;
     pub use escrow::*;
     pub use milestone::*;
+    pub use proposal::*;
+    pub use protocol::*;
+    pub use role::*;
+    pub use upgrade::*;
     pub mod validators {
         pub mod dispute;
         pub mod escrow;
         pub mod milestone;
+        pub mod protocol;
+        pub mod upgrade;
     }
 }
 mod error;
@@ -36,6 +55,8 @@ mod modules {
 
 /// This module is currently Work In Progress.
 mod storage {
+    pub mod escrow;
+    pub mod legacy;
     pub mod types;
 }
 mod tests {
@@ -44,4 +65,9 @@ mod tests {
 }
 
 pub use crate::contract::{EscrowContract, EscrowContractClient};
-pub use crate::storage::types::{Escrow, Flags, Milestone, Roles, Trustline};
+pub use crate::storage::types::{
+    Appeal, AppealWindow, Arbitration, ArbitrationPanel, Deadline, DisputeRecord, DisputeTarget,
+    DisputeVote, DisputeVoting, Deposit, Escrow, EscrowUpdateProposal, Evidence,
+    EvidenceSubmission, Flags, Milestone, MilestoneStatus, Payee, Payout, PayoutQuote,
+    PendingResolution, ProtocolConfig, Role, Roles, Trustline,
+};
@@ -6,8 +6,7 @@ use crate::{
     },
 };
 
-const TRUSTLESS_WORK_FEE_BPS: u32 = 30;
-const BASIS_POINTS_DENOMINATOR: i128 = 10000;
+pub const BASIS_POINTS_DENOMINATOR: i128 = 10000;
 
 #[derive(Debug, Clone)]
 pub struct StandardFeeResult {
@@ -19,6 +18,7 @@ pub struct StandardFeeResult {
 pub trait FeeCalculatorTrait {
     fn calculate_standard_fees(
         total_amount: i128,
+        protocol_fee_bps: u32,
         platform_fee_bps: u32,
     ) -> Result<StandardFeeResult, ContractError>;
 }
@@ -29,13 +29,11 @@ pub struct FeeCalculator;
 impl FeeCalculatorTrait for FeeCalculator {
     fn calculate_standard_fees(
         total_amount: i128,
+        protocol_fee_bps: u32,
         platform_fee_bps: u32,
     ) -> Result<StandardFeeResult, ContractError> {
-        let trustless_work_fee = SafeMath::safe_mul_div(
-            total_amount,
-            TRUSTLESS_WORK_FEE_BPS,
-            BASIS_POINTS_DENOMINATOR,
-        )?;
+        let trustless_work_fee =
+            SafeMath::safe_mul_div(total_amount, protocol_fee_bps, BASIS_POINTS_DENOMINATOR)?;
         let platform_fee =
             SafeMath::safe_mul_div(total_amount, platform_fee_bps, BASIS_POINTS_DENOMINATOR)?;
 
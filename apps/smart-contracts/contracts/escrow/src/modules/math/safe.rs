@@ -4,6 +4,7 @@ pub struct SafeMath;
 
 pub trait SafeArithmetic {
     fn safe_mul_div(amount: i128, multiplier: u32, divisor: i128) -> Result<i128, ContractError>;
+    fn safe_share(amount: i128, part: i128, total: i128) -> Result<i128, ContractError>;
 }
 
 impl SafeArithmetic for SafeMath {
@@ -14,4 +15,12 @@ impl SafeArithmetic for SafeMath {
             .checked_div(divisor)
             .ok_or(ContractError::DivisionError)
     }
+
+    fn safe_share(amount: i128, part: i128, total: i128) -> Result<i128, ContractError> {
+        amount
+            .checked_mul(part)
+            .ok_or(ContractError::Overflow)?
+            .checked_div(total)
+            .ok_or(ContractError::DivisionError)
+    }
 }
@@ -0,0 +1,202 @@
+use soroban_sdk::{Address, Env, Error, Map, Symbol, TryFromVal, Val, Vec};
+
+use crate::error::ContractError;
+use crate::storage::legacy::LegacyEscrow;
+use crate::storage::types::{DataKey, Escrow, EscrowHeader, Milestone};
+
+/// Reads and writes the escrow split into a header in instance storage and one persistent
+/// entry per milestone. Escrows deployed with the legacy single-entry layout are still
+/// readable, and the first write (or `migrate`) moves them to the split layout.
+pub struct EscrowStorage;
+
+impl EscrowStorage {
+    pub fn has(e: &Env) -> bool {
+        let storage = e.storage().instance();
+        storage.has(&DataKey::Header) || storage.has(&DataKey::Escrow)
+    }
+
+    pub fn is_legacy(e: &Env) -> bool {
+        e.storage().instance().has(&DataKey::Escrow)
+    }
+
+    pub fn load(e: &Env) -> Result<Escrow, Error> {
+        if let Some(escrow) = Self::load_legacy(e) {
+            return Ok(escrow);
+        }
+
+        let header = Self::load_header(e)?;
+        let mut milestones: Vec<Milestone> = Vec::new(e);
+        for index in 0..header.milestone_count {
+            milestones.push_back(Self::load_milestone(e, index)?);
+        }
+
+        Ok(Escrow {
+            engagement_id: header.engagement_id,
+            title: header.title,
+            roles: header.roles,
+            description: header.description,
+            amount: header.amount,
+            platform_fee: header.platform_fee,
+            milestones,
+            flags: header.flags,
+            trustline: header.trustline,
+            receiver_memo: header.receiver_memo,
+            deadline: header.deadline,
+            payees: header.payees,
+            co_approvers: header.co_approvers,
+            approval_threshold: header.approval_threshold,
+            arbitration: header.arbitration,
+            appeal: header.appeal,
+            review_period: header.review_period,
+        })
+    }
+
+    /// Writes the whole escrow: header and every milestone. Entries left over from a
+    /// previously longer milestone list are removed.
+    pub fn save(e: &Env, escrow: &Escrow) {
+        let previous_count = e
+            .storage()
+            .instance()
+            .get::<_, EscrowHeader>(&DataKey::Header)
+            .map(|header| header.milestone_count)
+            .unwrap_or(0);
+
+        let persistent = e.storage().persistent();
+        for (index, milestone) in escrow.milestones.iter().enumerate() {
+            persistent.set(&DataKey::Milestone(index as u32), &milestone);
+        }
+        for index in escrow.milestones.len()..previous_count {
+            persistent.remove(&DataKey::Milestone(index));
+        }
+
+        Self::write_header(e, escrow);
+        e.storage().instance().remove(&DataKey::Escrow);
+    }
+
+    /// Writes the header only. Milestone changes must be saved separately.
+    pub fn save_header(e: &Env, escrow: &Escrow) {
+        if Self::is_legacy(e) {
+            return Self::save(e, escrow);
+        }
+        Self::write_header(e, escrow);
+    }
+
+    /// Writes a single milestone of `escrow`, which must already hold the updated value.
+    pub fn save_milestone(e: &Env, escrow: &Escrow, index: u32) -> Result<(), Error> {
+        if Self::is_legacy(e) {
+            Self::save(e, escrow);
+            return Ok(());
+        }
+
+        let milestone = escrow
+            .milestones
+            .get(index)
+            .ok_or(ContractError::InvalidMileStoneIndex)?;
+        e.storage()
+            .persistent()
+            .set(&DataKey::Milestone(index), &milestone);
+        Ok(())
+    }
+
+    /// Writes the given milestones of `escrow`. A legacy escrow is written once, whatever the
+    /// number of milestones.
+    pub fn save_milestones(e: &Env, escrow: &Escrow, indexes: &Vec<u32>) -> Result<(), Error> {
+        if Self::is_legacy(e) {
+            Self::save(e, escrow);
+            return Ok(());
+        }
+
+        for index in indexes.iter() {
+            Self::save_milestone(e, escrow, index)?;
+        }
+        Ok(())
+    }
+
+    /// Moves a legacy escrow to the split layout.
+    pub fn migrate(e: &Env) -> Result<(), Error> {
+        let escrow = Self::load_legacy(e).ok_or(ContractError::StorageAlreadyMigrated)?;
+
+        Self::save(e, &escrow);
+        Ok(())
+    }
+
+    /// Extends the TTL of the instance storage, header included. Milestone entries are
+    /// extended one at a time through `extend_milestone_ttl`.
+    pub fn extend_ttl(e: &Env, threshold: u32, extend_to: u32) {
+        e.storage().instance().extend_ttl(threshold, extend_to);
+    }
+
+    pub fn extend_milestone_ttl(e: &Env, index: u32, threshold: u32, extend_to: u32) {
+        if Self::is_legacy(e) {
+            return;
+        }
+
+        e.storage()
+            .persistent()
+            .extend_ttl(&DataKey::Milestone(index), threshold, extend_to);
+    }
+
+    /// The platform address of the stored escrow, whichever version wrote it. Only the roles
+    /// of the raw header are decoded, so the address can be checked before the state is
+    /// migrated to the current layout.
+    pub fn load_platform_address(e: &Env) -> Result<Address, Error> {
+        let storage = e.storage().instance();
+        let header: Map<Symbol, Val> = storage
+            .get(&DataKey::Header)
+            .or_else(|| storage.get(&DataKey::Escrow))
+            .ok_or(ContractError::EscrowNotFound)?;
+
+        header
+            .get(Symbol::new(e, "roles"))
+            .and_then(|roles| Map::<Symbol, Val>::try_from_val(e, &roles).ok())
+            .and_then(|roles| roles.get(Symbol::new(e, "platform_address")))
+            .and_then(|platform| Address::try_from_val(e, &platform).ok())
+            .ok_or(ContractError::EscrowNotFound.into())
+    }
+
+    /// Decodes the single entry written by the legacy code and converts it to the current
+    /// layout.
+    fn load_legacy(e: &Env) -> Option<Escrow> {
+        e.storage()
+            .instance()
+            .get::<_, LegacyEscrow>(&DataKey::Escrow)
+            .map(|legacy| legacy.into_escrow(e))
+    }
+
+    fn load_header(e: &Env) -> Result<EscrowHeader, Error> {
+        e.storage()
+            .instance()
+            .get(&DataKey::Header)
+            .ok_or(ContractError::EscrowNotFound.into())
+    }
+
+    fn load_milestone(e: &Env, index: u32) -> Result<Milestone, Error> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::Milestone(index))
+            .ok_or(ContractError::EscrowNotFound.into())
+    }
+
+    fn write_header(e: &Env, escrow: &Escrow) {
+        let header = EscrowHeader {
+            engagement_id: escrow.engagement_id.clone(),
+            title: escrow.title.clone(),
+            roles: escrow.roles.clone(),
+            description: escrow.description.clone(),
+            amount: escrow.amount,
+            platform_fee: escrow.platform_fee,
+            milestone_count: escrow.milestones.len(),
+            flags: escrow.flags.clone(),
+            trustline: escrow.trustline.clone(),
+            receiver_memo: escrow.receiver_memo,
+            deadline: escrow.deadline.clone(),
+            payees: escrow.payees.clone(),
+            co_approvers: escrow.co_approvers.clone(),
+            approval_threshold: escrow.approval_threshold,
+            arbitration: escrow.arbitration.clone(),
+            appeal: escrow.appeal.clone(),
+            review_period: escrow.review_period,
+        };
+        e.storage().instance().set(&DataKey::Header, &header);
+    }
+}
@@ -0,0 +1,136 @@
+use soroban_sdk::{contracttype, Address, Env, String, Vec};
+
+use crate::storage::types::{
+    Appeal, Arbitration, Deadline, Escrow, Flags, Milestone, MilestoneStatus, Roles, Trustline,
+};
+
+// Layout of the escrow written under `DataKey::Escrow` by the code deployed before storage
+// was split. These types are frozen: they only exist to decode that entry, so they must not
+// change even when the current types do.
+
+#[contracttype(export = false)]
+#[derive(Clone)]
+pub struct LegacyEscrow {
+    pub engagement_id: String,
+    pub title: String,
+    pub roles: LegacyRoles,
+    pub description: String,
+    pub amount: i128,
+    pub platform_fee: u32,
+    pub milestones: Vec<LegacyMilestone>,
+    pub flags: LegacyFlags,
+    pub trustline: LegacyTrustline,
+    pub receiver_memo: i128,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone)]
+pub struct LegacyMilestone {
+    pub description: String,
+    pub status: String,
+    pub evidence: String,
+    pub approved: bool,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone)]
+pub struct LegacyRoles {
+    pub approver: Address,
+    pub service_provider: Address,
+    pub platform_address: Address,
+    pub release_signer: Address,
+    pub dispute_resolver: Address,
+    pub receiver: Address,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone)]
+pub struct LegacyFlags {
+    pub disputed: bool,
+    pub released: bool,
+    pub resolved: bool,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone)]
+pub struct LegacyTrustline {
+    pub address: Address,
+}
+
+impl LegacyEscrow {
+    /// Converts the escrow to the current layout. Legacy escrows were paid out as a whole,
+    /// so the amount is split evenly between the milestones, the last one taking the
+    /// remainder, and a release or resolution of the escrow settles every milestone. The
+    /// free-form status has no typed counterpart: an approved milestone becomes `Approved`
+    /// and any other starts over as `Pending`. Fields added since default to their off state.
+    pub fn into_escrow(self, e: &Env) -> Escrow {
+        let milestone_count = self.milestones.len() as i128;
+        let mut remaining = self.amount;
+
+        let mut milestones: Vec<Milestone> = Vec::new(e);
+        for (index, legacy) in self.milestones.iter().enumerate() {
+            let amount = if index as i128 == milestone_count - 1 {
+                remaining
+            } else {
+                self.amount / milestone_count
+            };
+            remaining -= amount;
+
+            let status = if self.flags.released {
+                MilestoneStatus::Released
+            } else if legacy.approved {
+                MilestoneStatus::Approved
+            } else {
+                MilestoneStatus::Pending
+            };
+
+            milestones.push_back(Milestone {
+                description: legacy.description,
+                status,
+                evidence: legacy.evidence,
+                amount,
+                approved: legacy.approved,
+                released: self.flags.released,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: self.flags.resolved,
+                depends_on: Vec::new(e),
+            });
+        }
+
+        Escrow {
+            engagement_id: self.engagement_id,
+            title: self.title,
+            roles: Roles {
+                approver: self.roles.approver,
+                service_provider: self.roles.service_provider,
+                platform_address: self.roles.platform_address,
+                release_signer: self.roles.release_signer,
+                dispute_resolver: self.roles.dispute_resolver,
+                receiver: self.roles.receiver,
+            },
+            description: self.description,
+            amount: self.amount,
+            platform_fee: self.platform_fee,
+            milestones,
+            flags: Flags {
+                disputed: self.flags.disputed,
+                released: self.flags.released,
+                resolved: self.flags.resolved,
+                cancelled: false,
+            },
+            trustline: Trustline {
+                address: self.trustline.address,
+            },
+            receiver_memo: self.receiver_memo,
+            deadline: Deadline::None,
+            payees: Vec::new(e),
+            co_approvers: Vec::new(e),
+            approval_threshold: 1,
+            arbitration: Arbitration::None,
+            appeal: Appeal::None,
+            review_period: None,
+        }
+    }
+}
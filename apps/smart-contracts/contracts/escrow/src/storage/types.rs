@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, String, Vec};
+use soroban_sdk::{contracttype, Address, BytesN, Map, String, Vec};
 
 #[contracttype]
 #[derive(Clone, PartialEq, Eq)]
@@ -13,15 +13,83 @@ pub struct Escrow {
     pub flags: Flags,
     pub trustline: Trustline,
     pub receiver_memo: i128,
+    pub deadline: Deadline,
+    pub payees: Vec<Payee>,
+    /// Approvers besides `roles.approver`. A milestone is approved once `approval_threshold`
+    /// of them, `roles.approver` included, have approved it.
+    pub co_approvers: Vec<Address>,
+    pub approval_threshold: u32,
+    pub arbitration: Arbitration,
+    pub appeal: Appeal,
+    /// Seconds the approver has to review a submitted milestone before anyone may approve
+    /// it through `auto_approve`. `None` disables auto-approval.
+    pub review_period: Option<u64>,
+}
+
+/// A party that receives `share_bps` basis points of what is paid out to the receiver side.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payee {
+    pub address: Address,
+    pub share_bps: u32,
+}
+
+/// One recipient's line of a quote: `gross` is its part of the amount before fees,
+/// `fee_share` the part of the fees it bears and `amount` what it is actually paid.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payout {
+    pub recipient: Address,
+    pub gross: i128,
+    pub fee_share: i128,
+    pub amount: i128,
+}
+
+/// What a release or a dispute resolution pays: the two fees and the net amount per recipient.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayoutQuote {
+    pub protocol_fee: i128,
+    pub platform_fee: i128,
+    pub payouts: Vec<Payout>,
+}
+
+/// What a token transfer out of the escrow pays for.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferKind {
+    ProtocolFee,
+    PlatformFee,
+    Payout,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Milestone {
     pub description: String,
-    pub status: String,
+    pub status: MilestoneStatus,
     pub evidence: String,
+    pub amount: i128,
     pub approved: bool,
+    pub released: bool,
+    pub rejection_reason: Option<String>,
+    pub rejection_count: u32,
+    pub disputed: bool,
+    pub resolved: bool,
+    /// Indexes of the milestones that must be approved before this one can be submitted
+    /// or approved.
+    pub depends_on: Vec<u32>,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MilestoneStatus {
+    Pending,
+    InProgress,
+    Submitted,
+    Rejected,
+    Approved,
+    Released,
 }
 
 #[contracttype]
@@ -35,12 +103,101 @@ pub struct Roles {
     pub receiver: Address,
 }
 
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Approver,
+    ServiceProvider,
+    PlatformAddress,
+    ReleaseSigner,
+    DisputeResolver,
+    Receiver,
+}
+
 #[contracttype]
 #[derive(Clone, PartialEq, Eq)]
 pub struct Flags {
     pub disputed: bool,
     pub released: bool,
     pub resolved: bool,
+    pub cancelled: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Deadline {
+    None,
+    Ledger(u32),
+    Timestamp(u64),
+}
+
+/// How disputes are settled. `None` leaves them to `roles.dispute_resolver` alone.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Arbitration {
+    None,
+    Panel(ArbitrationPanel),
+}
+
+/// Resolvers vote on a distribution, which is executed as soon as `quorum` of them submit
+/// the same one. If no distribution reaches quorum within `voting_period` seconds of the
+/// dispute being opened, `roles.dispute_resolver` settles it as lead resolver.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArbitrationPanel {
+    pub resolvers: Vec<Address>,
+    pub quorum: u32,
+    pub voting_period: u64,
+}
+
+/// Whether dispute resolutions are paid out at once or can first be appealed.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Appeal {
+    None,
+    Window(AppealWindow),
+}
+
+/// A resolution stays locked for `ledgers` ledgers, during which the approver or the service
+/// provider can appeal it to `appeal_resolver`, whose decision is final.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppealWindow {
+    pub ledgers: u32,
+    pub appeal_resolver: Address,
+}
+
+/// A resolution that has been decided but not paid out yet.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingResolution {
+    pub decided_by: Address,
+    pub distributions: Map<Address, i128>,
+    pub total: i128,
+    pub appealable_until: u32,
+    pub appealed: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisputeTarget {
+    Escrow,
+    Milestone(u32),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeVote {
+    pub resolver: Address,
+    pub distributions: Map<Address, i128>,
+}
+
+/// Votes cast by the panel on an open dispute. Each resolver holds at most one vote.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeVoting {
+    pub deadline: u64,
+    pub votes: Vec<DisputeVote>,
 }
 
 #[contracttype]
@@ -57,9 +214,140 @@ pub struct AddressBalance {
     pub trustline_decimals: u32,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deposit {
+    pub depositor: Address,
+    pub amount: i128,
+}
+
+/// Evidence as submitted by the service provider: where it lives and the hash of its content.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvidenceSubmission {
+    pub uri: String,
+    pub hash: BytesN<32>,
+}
+
+/// An entry of a milestone's append-only evidence log.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Evidence {
+    pub submitter: Address,
+    pub timestamp: u64,
+    pub uri: String,
+    pub hash: BytesN<32>,
+}
+
+/// Why the escrow was disputed, and the statements its parties submitted while it was.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeRecord {
+    pub opened_by: Address,
+    pub opened_at: u64,
+    pub reason: String,
+    pub statements: Vec<Evidence>,
+}
+
+/// Fee charged by Trustless Work on every payout and the address that receives it.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolConfig {
+    pub fee_bps: u32,
+    pub treasury: Address,
+}
+
+/// Everything about an escrow except its milestones. Kept in instance storage, while each
+/// milestone lives in its own persistent entry under `DataKey::Milestone`.
+#[contracttype]
+#[derive(Clone, PartialEq, Eq)]
+pub struct EscrowHeader {
+    pub engagement_id: String,
+    pub title: String,
+    pub roles: Roles,
+    pub description: String,
+    pub amount: i128,
+    pub platform_fee: u32,
+    pub milestone_count: u32,
+    pub flags: Flags,
+    pub trustline: Trustline,
+    pub receiver_memo: i128,
+    pub deadline: Deadline,
+    pub payees: Vec<Payee>,
+    pub co_approvers: Vec<Address>,
+    pub approval_threshold: u32,
+    pub arbitration: Arbitration,
+    pub appeal: Appeal,
+    pub review_period: Option<u64>,
+}
+
+/// Escrow properties staged by the platform, applied once both the approver and the
+/// service provider have accepted them, as long as `expires_at` has not passed.
+#[contracttype]
+#[derive(Clone, PartialEq, Eq)]
+pub struct EscrowUpdateProposal {
+    pub escrow: Escrow,
+    pub proposer: Address,
+    pub expires_at: u64,
+    pub approver_accepted: bool,
+    pub service_provider_accepted: bool,
+}
+
+/// What an update changes: the new value of every escrow property that differs, and every
+/// milestone that was added or changed. Milestones were removed from the end when
+/// `milestone_count` is below the previous count.
+#[contracttype]
+#[derive(Clone)]
+pub struct EscrowChanges {
+    pub properties: Vec<EscrowPropertyChange>,
+    pub milestones: Map<u32, Milestone>,
+    pub milestone_count: u32,
+}
+
+/// An escrow property changed by an update, with its new value. Contract types cannot be
+/// boxed, so the variants keep their values inline.
+#[contracttype]
+#[derive(Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum EscrowPropertyChange {
+    EngagementId(String),
+    Title(String),
+    Roles(Roles),
+    Description(String),
+    Amount(i128),
+    PlatformFee(u32),
+    Flags(Flags),
+    Trustline(Trustline),
+    ReceiverMemo(i128),
+    Deadline(Deadline),
+    Payees(Vec<Payee>),
+    CoApprovers(Vec<Address>),
+    ApprovalThreshold(u32),
+    Arbitration(Arbitration),
+    Appeal(Appeal),
+    ReviewPeriod(Option<u64>),
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
+    /// Legacy layout: the whole escrow, milestones included, stored as a single instance entry.
     Escrow,
+    Header,
+    Milestone(u32),
+    Version,
     Admin,
+    ProtocolConfig,
+    Depositors,
+    Deposit(Address),
+    MilestoneRevision(u32),
+    EvidenceCount(u32, u32),
+    Evidence(u32, u32, u32),
+    UpdateProposal,
+    MilestoneApprovals(u32),
+    DisputeVoting(DisputeTarget),
+    DisputeCount,
+    Dispute(u32),
+    PendingResolution(DisputeTarget),
+    MilestoneSubmittedAt(u32),
 }
@@ -4,9 +4,27 @@ extern crate std;
 
 use crate::contract::EscrowContract;
 use crate::contract::EscrowContractClient;
-use crate::storage::types::{Escrow, Flags, Milestone, Roles, Trustline};
-
-use soroban_sdk::{testutils::Address as _, token, vec, Address, Env, Map, String};
+use crate::core::{
+    DEFAULT_PROTOCOL_FEE_BPS, MAX_PROTOCOL_FEE_BPS, UPDATE_PROPOSAL_VALIDITY_SECONDS,
+};
+use crate::error::{ContractError, FundsError, WorkflowError, DisputeError};
+use crate::storage::legacy::{
+    LegacyEscrow, LegacyFlags, LegacyMilestone, LegacyRoles, LegacyTrustline,
+};
+use crate::storage::types::{
+    Appeal, AppealWindow, Arbitration, ArbitrationPanel, DataKey, Deadline, DisputeTarget, Escrow,
+    EscrowChanges, EscrowPropertyChange, EvidenceSubmission, Flags, Milestone, MilestoneStatus,
+    Payee, Payout, ProtocolConfig, Role, Roles, TransferKind, Trustline,
+};
+
+use soroban_sdk::{
+    testutils::{
+        storage::Persistent as _, Address as _, Events, Ledger, MockAuth, MockAuthInvoke,
+    },
+    token, vec,
+    xdr::ScErrorType,
+    Address, BytesN, Env, Error, IntoVal, InvokeError, Map, String, Symbol, TryFromVal, Val, Vec,
+};
 use token::Client as TokenClient;
 use token::StellarAssetClient as TokenAdminClient;
 // use test_token::token::{Token, TokenClient};
@@ -19,15 +37,209 @@ fn create_usdc_token<'a>(e: &Env, admin: &Address) -> (TokenClient<'a>, TokenAdm
     )
 }
 
+fn create_evidence(e: &Env, uri: &str, seed: u8) -> EvidenceSubmission {
+    EvidenceSubmission {
+        uri: String::from_str(e, uri),
+        hash: BytesN::from_array(e, &[seed; 32]),
+    }
+}
+
+/// Has the service provider submit the given milestones. Milestones always start `Pending`.
+fn submit_milestones(client: &EscrowContractClient, service_provider: &Address, indexes: &[i128]) {
+    for index in indexes {
+        client.change_milestone_status(
+            index,
+            &MilestoneStatus::Submitted,
+            &None,
+            service_provider,
+        );
+    }
+}
+
 struct TestData<'a> {
     client: EscrowContractClient<'a>,
+    admin: Address,
+    treasury: Address,
 }
 
-fn create_escrow_contract<'a>(env: &Env) -> TestData {
+/// Registers the contract with a fresh protocol admin and treasury.
+fn create_escrow_contract<'a>(env: &Env) -> TestData<'a> {
     env.mock_all_auths();
-    let client = EscrowContractClient::new(env, &env.register(EscrowContract {}, ()));
+    let admin = Address::generate(env);
+    let treasury = Address::generate(env);
+    let client = EscrowContractClient::new(
+        env,
+        &env.register(EscrowContract {}, (admin.clone(), treasury.clone())),
+    );
+
+    TestData {
+        client,
+        admin,
+        treasury,
+    }
+}
+
+/// A contract, a token and one address per role, with an escrow that tests adjust before
+/// initializing it. The escrow has one pending milestone per amount, pays the service
+/// provider and charges no platform fee.
+struct EscrowSetup<'a> {
+    env: Env,
+    client: EscrowContractClient<'a>,
+    token: TokenClient<'a>,
+    token_admin: TokenAdminClient<'a>,
+    treasury: Address,
+    approver: Address,
+    service_provider: Address,
+    platform: Address,
+    release_signer: Address,
+    dispute_resolver: Address,
+    escrow: Escrow,
+}
+
+impl EscrowSetup<'_> {
+    fn new(milestone_amounts: &[i128]) -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let (token, token_admin) = create_usdc_token(&env, &admin);
+        let test_data = create_escrow_contract(&env);
+
+        let approver = Address::generate(&env);
+        let service_provider = Address::generate(&env);
+        let platform = Address::generate(&env);
+        let release_signer = Address::generate(&env);
+        let dispute_resolver = Address::generate(&env);
+
+        let mut milestones = vec![&env];
+        for amount in milestone_amounts {
+            milestones.push_back(create_milestone(&env, *amount));
+        }
+
+        let escrow = Escrow {
+            engagement_id: String::from_str(&env, "test_escrow"),
+            title: String::from_str(&env, "Test Escrow"),
+            description: String::from_str(&env, "Test Escrow Description"),
+            roles: Roles {
+                approver: approver.clone(),
+                service_provider: service_provider.clone(),
+                platform_address: platform.clone(),
+                release_signer: release_signer.clone(),
+                dispute_resolver: dispute_resolver.clone(),
+                receiver: service_provider.clone(),
+            },
+            amount: milestone_amounts.iter().sum(),
+            platform_fee: 0,
+            milestones,
+            flags: Flags {
+                disputed: false,
+                released: false,
+                resolved: false,
+                cancelled: false,
+            },
+            trustline: Trustline {
+                address: token.address.clone(),
+            },
+            receiver_memo: 0,
+            deadline: Deadline::None,
+            payees: vec![&env],
+            co_approvers: vec![&env],
+            approval_threshold: 1,
+            arbitration: Arbitration::None,
+            appeal: Appeal::None,
+            review_period: None,
+        };
+
+        EscrowSetup {
+            env,
+            client: test_data.client,
+            token,
+            token_admin,
+            treasury: test_data.treasury,
+            approver,
+            service_provider,
+            platform,
+            release_signer,
+            dispute_resolver,
+            escrow,
+        }
+    }
+
+    fn initialize(&self) {
+        self.client.initialize_escrow(&self.escrow);
+    }
+
+    /// Initializes the escrow and has the approver deposit its full amount.
+    fn initialize_and_fund(&self) {
+        self.initialize();
+        self.token_admin.mint(&self.approver, &self.escrow.amount);
+        self.client
+            .fund_escrow(&self.approver, &self.escrow, &self.escrow.amount);
+    }
+
+    fn submit(&self, milestone_index: i128) {
+        self.client.change_milestone_status(
+            &milestone_index,
+            &MilestoneStatus::Submitted,
+            &None,
+            &self.service_provider,
+        );
+    }
+
+    fn milestone(&self, index: u32) -> Milestone {
+        self.client.get_escrow().milestones.get(index).unwrap()
+    }
+
+    fn set_depends_on(&mut self, index: u32, depends_on: &[u32]) {
+        let mut milestone = self.escrow.milestones.get(index).unwrap();
+        milestone.depends_on = Vec::from_slice(&self.env, depends_on);
+        self.escrow.milestones.set(index, milestone);
+    }
+
+    /// Writes `status` to the stored milestone, for states the entrypoints cannot reach.
+    fn set_status(&self, index: u32, status: MilestoneStatus) {
+        self.env.as_contract(&self.client.address, || {
+            let storage = self.env.storage().persistent();
+            let key = DataKey::Milestone(index);
+            let mut milestone: Milestone = storage.get(&key).unwrap();
+            milestone.status = status;
+            storage.set(&key, &milestone);
+        });
+    }
+}
+
+fn create_milestone(e: &Env, amount: i128) -> Milestone {
+    Milestone {
+        description: String::from_str(e, "Milestone"),
+        status: MilestoneStatus::Pending,
+        evidence: String::from_str(e, ""),
+        amount,
+        approved: false,
+        released: false,
+        rejection_reason: None,
+        rejection_count: 0,
+        disputed: false,
+        resolved: false,
+        depends_on: vec![e],
+    }
+}
+
+fn create_distributions(e: &Env, shares: &[(&Address, i128)]) -> Map<Address, i128> {
+    let mut distributions = Map::new(e);
+    for (address, amount) in shares {
+        distributions.set((*address).clone(), *amount);
+    }
+    distributions
+}
+
+/// Addresses that signed the last invocation.
+fn last_signers(env: &Env) -> std::vec::Vec<Address> {
+    env.auths().iter().map(|(address, _)| address.clone()).collect()
+}
 
-    TestData { client }
+/// Asserts that a `try_` call failed with the contract error `expected`.
+fn assert_error<T>(result: Result<T, Result<Error, InvokeError>>, expected: impl Into<Error>) {
+    assert_eq!(result.err(), Some(Ok(expected.into())));
 }
 
 #[test]
@@ -48,15 +260,29 @@ fn test_initialize_excrow() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
         Milestone {
             description: String::from_str(&env, "Second milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -77,6 +303,7 @@ fn test_initialize_excrow() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -88,12 +315,19 @@ fn test_initialize_excrow() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles,
-        amount: amount,
-        platform_fee: platform_fee,
-        milestones: milestones,
+        amount,
+        platform_fee,
+        milestones,
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
@@ -150,15 +384,29 @@ fn test_update_escrow() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
         Milestone {
             description: String::from_str(&env, "Second milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -177,6 +425,7 @@ fn test_update_escrow() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -189,12 +438,19 @@ fn test_update_escrow() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles: roles.clone(),
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: initial_milestones.clone(),
         flags: flags.clone(),
         trustline: trustline.clone(),
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
@@ -207,21 +463,42 @@ fn test_update_escrow() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone updated"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
         Milestone {
             description: String::from_str(&env, "Second milestone updated"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
         Milestone {
             description: String::from_str(&env, "Third milestone new"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -236,6 +513,13 @@ fn test_update_escrow() {
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     // Update escrow properties
@@ -293,9 +577,16 @@ fn test_update_escrow_platform_fee_too_high() {
         &env,
         Milestone {
             description: String::from_str(&env, "M1"),
-            status: String::from_str(&env, "pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "e"),
+            amount,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -311,7 +602,12 @@ fn test_update_escrow_platform_fee_too_high() {
         receiver: service_provider_address.clone(),
     };
 
-    let flags: Flags = Flags { disputed: false, released: false, resolved: false };
+    let flags: Flags = Flags {
+        disputed: false,
+        released: false,
+        resolved: false,
+        cancelled: false,
+    };
 
     let initial_escrow: Escrow = Escrow {
         engagement_id: String::from_str(&env, "pf_valid"),
@@ -324,6 +620,13 @@ fn test_update_escrow_platform_fee_too_high() {
         flags: flags.clone(),
         trustline: trustline.clone(),
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
@@ -342,6 +645,13 @@ fn test_update_escrow_platform_fee_too_high() {
         flags: flags.clone(),
         trustline: trustline.clone(),
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let res = client.try_update_escrow(&platform_address, &invalid_update);
@@ -367,9 +677,16 @@ fn test_initialize_escrow_platform_fee_too_high() {
         &env,
         Milestone {
             description: String::from_str(&env, "M1"),
-            status: String::from_str(&env, "pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "e"),
+            amount,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -385,7 +702,12 @@ fn test_initialize_escrow_platform_fee_too_high() {
         receiver: service_provider_address.clone(),
     };
 
-    let flags: Flags = Flags { disputed: false, released: false, resolved: false };
+    let flags: Flags = Flags {
+        disputed: false,
+        released: false,
+        resolved: false,
+        cancelled: false,
+    };
 
     let invalid_escrow: Escrow = Escrow {
         engagement_id: String::from_str(&env, "pf_invalid_init"),
@@ -398,6 +720,13 @@ fn test_initialize_escrow_platform_fee_too_high() {
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
@@ -428,15 +757,29 @@ fn test_append_milestones_with_funds() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
         Milestone {
             description: String::from_str(&env, "Second milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -453,6 +796,7 @@ fn test_append_milestones_with_funds() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -465,12 +809,19 @@ fn test_append_milestones_with_funds() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles: roles.clone(),
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: initial_milestones.clone(),
         flags: flags.clone(),
         trustline: trustline.clone(),
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
@@ -489,9 +840,16 @@ fn test_append_milestones_with_funds() {
         initial_escrow_properties.milestones.get(1).unwrap(),
         Milestone {
             description: String::from_str(&env, "Third milestone new"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -500,15 +858,43 @@ fn test_append_milestones_with_funds() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles: roles.clone(),
-        amount: amount,
-        platform_fee: platform_fee,
+        amount: amount + amount / 2,
+        platform_fee,
         milestones: updated_milestones.clone(),
         flags: flags.clone(),
         trustline: trustline.clone(),
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
-    escrow_approver.update_escrow(&platform_address, &updated_escrow_properties);
+    // A funded escrow can no longer be updated by the platform alone
+    let result = escrow_approver.try_update_escrow(&platform_address, &updated_escrow_properties);
+    assert_error(result, WorkflowError::EscrowUpdateRequiresConsent);
+
+    escrow_approver.propose_escrow_update(&platform_address, &updated_escrow_properties);
+    escrow_approver.accept_escrow_update(&approver_address);
+
+    // One acceptance is not enough
+    let proposal = escrow_approver.get_escrow_update_proposal();
+    assert!(proposal.approver_accepted);
+    assert!(!proposal.service_provider_accepted);
+    assert!(proposal.escrow == updated_escrow_properties);
+    assert_eq!(escrow_approver.get_escrow().milestones.len(), 2);
+
+    let outsider = Address::generate(&env);
+    let result = escrow_approver.try_accept_escrow_update(&outsider);
+    assert_error(result, WorkflowError::UnauthorizedToAcceptUpdate);
+
+    escrow_approver.accept_escrow_update(&service_provider_address);
+
+    let result = escrow_approver.try_get_escrow_update_proposal();
+    assert_error(result, WorkflowError::UpdateProposalNotFound);
 
     let escrow = escrow_approver.get_escrow();
     assert_eq!(escrow.milestones.len(), 3);
@@ -519,7 +905,7 @@ fn test_append_milestones_with_funds() {
     assert_eq!(escrow.title, initial_escrow_properties.title);
     assert_eq!(escrow.description, initial_escrow_properties.description);
     assert!(escrow.roles == initial_escrow_properties.roles);
-    assert_eq!(escrow.amount, initial_escrow_properties.amount);
+    assert_eq!(escrow.amount, updated_escrow_properties.amount);
     assert_eq!(escrow.platform_fee, initial_escrow_properties.platform_fee);
     assert!(escrow.flags == initial_escrow_properties.flags);
     assert!(escrow.trustline == initial_escrow_properties.trustline);
@@ -550,15 +936,29 @@ fn test_append_milestones_with_funds_and_existing_approved() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
         Milestone {
             description: String::from_str(&env, "Second milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -575,6 +975,7 @@ fn test_append_milestones_with_funds_and_existing_approved() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -587,12 +988,19 @@ fn test_append_milestones_with_funds_and_existing_approved() {
         title: String::from_str(&env, "Test Escrow Approved"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles: roles.clone(),
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: initial_milestones.clone(),
         flags: flags.clone(),
         trustline: trustline.clone(),
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
@@ -603,7 +1011,13 @@ fn test_append_milestones_with_funds_and_existing_approved() {
     token_admin.mint(&approver_address, &amount);
     escrow_client.fund_escrow(&approver_address, &initial_escrow_properties, &amount);
 
-    // Approve the first milestone
+    // Submit and approve the first milestone
+    escrow_client.change_milestone_status(
+        &0,
+        &MilestoneStatus::Submitted,
+        &None,
+        &service_provider_address,
+    );
     escrow_client.approve_milestone(&0, &approver_address);
     let after_approval = escrow_client.get_escrow();
     assert!(after_approval.milestones.get(0).unwrap().approved);
@@ -615,9 +1029,16 @@ fn test_append_milestones_with_funds_and_existing_approved() {
         after_approval.milestones.get(1).unwrap(),
         Milestone {
             description: String::from_str(&env, "Third milestone new"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -626,15 +1047,24 @@ fn test_append_milestones_with_funds_and_existing_approved() {
         title: String::from_str(&env, "Test Escrow Approved"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles: roles.clone(),
-        amount: amount,
-        platform_fee: platform_fee,
+        amount: amount + amount / 2,
+        platform_fee,
         milestones: updated_milestones.clone(),
         flags: flags.clone(),
         trustline: trustline.clone(),
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
-    escrow_client.update_escrow(&platform_address, &updated_escrow_properties);
+    escrow_client.propose_escrow_update(&platform_address, &updated_escrow_properties);
+    escrow_client.accept_escrow_update(&approver_address);
+    escrow_client.accept_escrow_update(&service_provider_address);
     let final_escrow = escrow_client.get_escrow();
 
     assert_eq!(final_escrow.milestones.len(), 3);
@@ -646,13 +1076,158 @@ fn test_append_milestones_with_funds_and_existing_approved() {
     assert_eq!(final_escrow.title, initial_escrow_properties.title);
     assert_eq!(final_escrow.description, initial_escrow_properties.description);
     assert!(final_escrow.roles == initial_escrow_properties.roles);
-    assert_eq!(final_escrow.amount, initial_escrow_properties.amount);
+    assert_eq!(final_escrow.amount, updated_escrow_properties.amount);
     assert_eq!(final_escrow.platform_fee, initial_escrow_properties.platform_fee);
     assert!(final_escrow.flags == initial_escrow_properties.flags);
     assert!(final_escrow.trustline == initial_escrow_properties.trustline);
     assert_eq!(final_escrow.receiver_memo, initial_escrow_properties.receiver_memo);
 }
 
+#[test]
+fn test_update_keeps_the_evidence_of_replaced_milestones() {
+    let setup = EscrowSetup::new(&[5_000_000, 5_000_000]);
+    setup.initialize();
+    for milestone_index in 0..2 {
+        setup.client.change_milestone_status(
+            &milestone_index,
+            &MilestoneStatus::Submitted,
+            &Some(create_evidence(&setup.env, "ipfs://evidence", 1)),
+            &setup.service_provider,
+        );
+    }
+
+    // Milestone 0 gets new terms and milestone 1 is dropped
+    let mut updated_properties = setup.escrow.clone();
+    updated_properties.milestones = vec![&setup.env, create_milestone(&setup.env, 10_000_000)];
+    setup
+        .client
+        .update_escrow(&setup.platform, &updated_properties);
+    assert_eq!(setup.client.get_milestone_revision(&0), 1);
+    assert!(setup.client.get_milestone_evidence(&0, &0, &10).is_empty());
+
+    // The evidence given for the previous terms stays on record
+    let previous = setup.client.get_milestone_evidence_at(&0, &0, &0, &10);
+    assert_eq!(previous.len(), 1);
+    assert_eq!(previous.get(0).unwrap().uri, String::from_str(&setup.env, "ipfs://evidence"));
+
+    // A milestone added back at the dropped index starts without evidence
+    updated_properties
+        .milestones
+        .push_back(create_milestone(&setup.env, 1_000_000));
+    updated_properties.amount = 11_000_000;
+    setup
+        .client
+        .update_escrow(&setup.platform, &updated_properties);
+    assert_eq!(setup.client.get_milestone_revision(&1), 1);
+    assert!(setup.client.get_milestone_evidence(&1, &0, &10).is_empty());
+    assert_eq!(setup.client.get_milestone_evidence_at(&1, &0, &0, &10).len(), 1);
+
+    // New evidence goes to the log of the current revision only
+    setup.client.change_milestone_status(
+        &1,
+        &MilestoneStatus::Submitted,
+        &Some(create_evidence(&setup.env, "ipfs://new-terms", 2)),
+        &setup.service_provider,
+    );
+    assert_eq!(setup.client.get_milestone_evidence(&1, &0, &10).len(), 1);
+    assert_eq!(setup.client.get_milestone_evidence_at(&1, &0, &0, &10).len(), 1);
+}
+
+#[test]
+fn test_update_changing_a_counterparty_requires_consent() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+
+    let mut updated_properties = setup.escrow.clone();
+    updated_properties.roles.service_provider = Address::generate(&setup.env);
+
+    // Replacing a counterparty needs consent even while the escrow is unfunded
+    let result = setup
+        .client
+        .try_update_escrow(&setup.platform, &updated_properties);
+    assert_error(result, WorkflowError::EscrowUpdateRequiresConsent);
+}
+
+#[test]
+fn test_accept_escrow_update_without_a_proposal() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+
+    let result = setup.client.try_accept_escrow_update(&setup.approver);
+    assert_error(result, WorkflowError::UpdateProposalNotFound);
+}
+
+#[test]
+fn test_update_proposal_applies_once_both_counterparties_accept() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+
+    let new_service_provider = Address::generate(&setup.env);
+    let mut updated_properties = setup.escrow.clone();
+    updated_properties.roles.service_provider = new_service_provider.clone();
+
+    setup.env.ledger().with_mut(|li| li.timestamp = 1_000);
+    setup
+        .client
+        .propose_escrow_update(&setup.platform, &updated_properties);
+    let proposal = setup.client.get_escrow_update_proposal();
+    assert_eq!(proposal.proposer, setup.platform);
+    assert_eq!(proposal.expires_at, 1_000 + UPDATE_PROPOSAL_VALIDITY_SECONDS);
+
+    setup.client.accept_escrow_update(&setup.service_provider);
+    assert!(setup.client.get_escrow().roles == setup.escrow.roles);
+
+    setup.client.accept_escrow_update(&setup.approver);
+    let escrow = setup.client.get_escrow();
+    assert_eq!(escrow.roles.service_provider, new_service_provider);
+    assert!(escrow == updated_properties);
+}
+
+#[test]
+fn test_expired_update_proposal_cannot_be_accepted() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+
+    let mut updated_properties = setup.escrow.clone();
+    updated_properties.roles.service_provider = Address::generate(&setup.env);
+
+    setup.env.ledger().with_mut(|li| li.timestamp = 1_000);
+    setup
+        .client
+        .propose_escrow_update(&setup.platform, &updated_properties);
+    setup.client.accept_escrow_update(&setup.approver);
+
+    setup
+        .env
+        .ledger()
+        .with_mut(|li| li.timestamp = 1_001 + UPDATE_PROPOSAL_VALIDITY_SECONDS);
+    let result = setup
+        .client
+        .try_accept_escrow_update(&setup.service_provider);
+    assert_error(result, WorkflowError::UpdateProposalExpired);
+    assert!(setup.client.get_escrow().roles == setup.escrow.roles);
+}
+
+#[test]
+fn test_proposing_an_update_again_resets_acceptances() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+
+    let mut updated_properties = setup.escrow.clone();
+    updated_properties.roles.service_provider = Address::generate(&setup.env);
+
+    setup
+        .client
+        .propose_escrow_update(&setup.platform, &updated_properties);
+    setup.client.accept_escrow_update(&setup.approver);
+    assert!(setup.client.get_escrow_update_proposal().approver_accepted);
+
+    setup
+        .client
+        .propose_escrow_update(&setup.platform, &updated_properties);
+    assert!(!setup.client.get_escrow_update_proposal().approver_accepted);
+}
+
 #[test]
 fn test_change_milestone_status_and_approved() {
     let env = Env::default();
@@ -672,15 +1247,29 @@ fn test_change_milestone_status_and_approved() {
         &env,
         Milestone {
             description: String::from_str(&env, "Milestone 1"),
-            status: String::from_str(&env, "in-progress"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
         Milestone {
             description: String::from_str(&env, "Milestone 2"),
-            status: String::from_str(&env, "in-progress"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -697,6 +1286,7 @@ fn test_change_milestone_status_and_approved() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -709,12 +1299,19 @@ fn test_change_milestone_status_and_approved() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles: roles.clone(),
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: initial_milestones.clone(),
         flags: flags.clone(),
         trustline: trustline.clone(),
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
@@ -723,10 +1320,10 @@ fn test_change_milestone_status_and_approved() {
     escrow_approver.initialize_escrow(&escrow_properties);
 
     // Change milestone status (valid case)
-    let new_status = String::from_str(&env, "completed");
-    let new_evidence = Some(String::from_str(&env, "New evidence"));
+    let new_status = MilestoneStatus::Submitted;
+    let new_evidence = Some(create_evidence(&env, "New evidence", 0));
     escrow_approver.change_milestone_status(
-        &(0 as i128),
+        &0_i128,
         &new_status,
         &new_evidence,
         &service_provider_address,
@@ -740,14 +1337,14 @@ fn test_change_milestone_status_and_approved() {
     );
 
     // Change milestone approved (valid case)
-    escrow_approver.approve_milestone(&(0 as i128), &approver_address);
+    escrow_approver.approve_milestone(&0_i128, &approver_address);
 
     let final_escrow = escrow_approver.get_escrow();
     assert!(final_escrow.milestones.get(0).unwrap().approved);
 
-    let invalid_index = 10 as i128;
-    let new_status = String::from_str(&env, "completed");
-    let new_evidence = Some(String::from_str(&env, "New evidence"));
+    let invalid_index = 10_i128;
+    let new_status = MilestoneStatus::Submitted;
+    let new_evidence = Some(create_evidence(&env, "New evidence", 0));
 
     let result = escrow_approver.try_change_milestone_status(
         &invalid_index,
@@ -764,7 +1361,7 @@ fn test_change_milestone_status_and_approved() {
 
     // Test for `change_status` by invalid service provider
     let result = escrow_approver.try_change_milestone_status(
-        &(0 as i128),
+        &0_i128,
         &new_status,
         &new_evidence,
         &unauthorized_address,
@@ -772,7 +1369,7 @@ fn test_change_milestone_status_and_approved() {
     assert!(result.is_err());
 
     // Test for `change_approved` by invalid approver
-    let result = escrow_approver.try_approve_milestone(&(0 as i128), &unauthorized_address);
+    let result = escrow_approver.try_approve_milestone(&0_i128, &unauthorized_address);
     assert!(result.is_err());
 }
 
@@ -788,12 +1385,11 @@ fn test_release_funds_successful_flow() {
     let release_signer_address = Address::generate(&env);
     let dispute_resolver_address = Address::generate(&env);
     let _receiver_address = Address::generate(&env);
-    let trustless_work_address = Address::generate(&env);
 
     let usdc_token = create_usdc_token(&env, &admin);
 
     let amount: i128 = 100_000_000;
-    usdc_token.1.mint(&approver_address, &(amount as i128));
+    usdc_token.1.mint(&approver_address, &{ amount });
 
     let platform_fee = 5 * 100;
 
@@ -801,15 +1397,29 @@ fn test_release_funds_successful_flow() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Completed"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
         Milestone {
             description: String::from_str(&env, "Second milestone"),
-            status: String::from_str(&env, "Completed"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -826,6 +1436,7 @@ fn test_release_funds_successful_flow() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -838,32 +1449,41 @@ fn test_release_funds_successful_flow() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles,
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: milestones.clone(),
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
     let escrow_approver = test_data.client;
+    let trustless_work_address = test_data.treasury;
 
     escrow_approver.initialize_escrow(&escrow_properties);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0, 1]);
 
     usdc_token
         .1
-        .mint(&escrow_approver.address, &(amount as i128));
+        .mint(&escrow_approver.address, &{ amount });
 
     escrow_approver.approve_milestone(&0, &approver_address);
     escrow_approver.approve_milestone(&1, &approver_address);
-    escrow_approver.release_funds(&release_signer_address, &trustless_work_address);
+    escrow_approver.release_funds(&release_signer_address);
 
-    let total_amount = amount as i128;
-    let trustless_work_commission = ((total_amount * 30) / 10000) as i128;
-    let platform_commission = (total_amount * platform_fee as i128) / 10000 as i128;
+    let total_amount = amount;
+    let trustless_work_commission = (total_amount * 30) / 10000;
+    let platform_commission = (total_amount * platform_fee as i128) / 10000_i128;
     let receiver_amount =
-        (total_amount - (trustless_work_commission + platform_commission)) as i128;
+        total_amount - (trustless_work_commission + platform_commission);
 
     assert_eq!(
         usdc_token.0.balance(&trustless_work_address),
@@ -909,7 +1529,6 @@ fn test_release_funds_milestones_incomplete() {
     let release_signer_address = Address::generate(&env);
     let dispute_resolver_address = Address::generate(&env);
     let _receiver_address = Address::generate(&env);
-    let trustless_work_address = Address::generate(&env);
 
     let usdc_token = create_usdc_token(&env, &admin);
 
@@ -921,15 +1540,29 @@ fn test_release_funds_milestones_incomplete() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Completed"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
         Milestone {
             description: String::from_str(&env, "Second milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
             approved: false, // Not approved yet
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -946,6 +1579,7 @@ fn test_release_funds_milestones_incomplete() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -957,26 +1591,34 @@ fn test_release_funds_milestones_incomplete() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles,
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: incomplete_milestones.clone(),
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
     let escrow_approver = test_data.client;
 
     escrow_approver.initialize_escrow(&escrow_properties);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0]);
 
     usdc_token
         .1
-        .mint(&escrow_approver.address, &(amount as i128));
+        .mint(&escrow_approver.address, &{ amount });
     escrow_approver.approve_milestone(&0, &approver_address);
     // Try to distribute earnings with incomplete milestones (should fail)
     let result =
-        escrow_approver.try_release_funds(&release_signer_address, &trustless_work_address);
+        escrow_approver.try_release_funds(&release_signer_address);
     assert!(result.is_err());
 }
 
@@ -993,12 +1635,11 @@ fn test_release_funds_same_receiver_as_provider() {
     let dispute_resolver_address = Address::generate(&env);
     // Use service_provider_address as receiver to test same-address case
     let _receiver_address = service_provider_address.clone();
-    let trustless_work_address = Address::generate(&env);
 
     let usdc_token = create_usdc_token(&env, &admin);
 
     let amount: i128 = 100_000_000;
-    usdc_token.1.mint(&approver_address, &(amount as i128));
+    usdc_token.1.mint(&approver_address, &{ amount });
 
     let platform_fee = 5 * 100;
 
@@ -1006,9 +1647,16 @@ fn test_release_funds_same_receiver_as_provider() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Completed"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -1025,6 +1673,7 @@ fn test_release_funds_same_receiver_as_provider() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -1037,31 +1686,40 @@ fn test_release_funds_same_receiver_as_provider() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles,
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: milestones.clone(),
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
     let escrow_approver = test_data.client;
+    let trustless_work_address = test_data.treasury;
 
     escrow_approver.initialize_escrow(&escrow_properties);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0]);
 
     usdc_token
         .1
-        .mint(&escrow_approver.address, &(amount as i128));
+        .mint(&escrow_approver.address, &{ amount });
 
     escrow_approver.approve_milestone(&0, &approver_address);
-    escrow_approver.release_funds(&release_signer_address, &trustless_work_address);
+    escrow_approver.release_funds(&release_signer_address);
 
-    let total_amount = amount as i128;
-    let trustless_work_commission = ((total_amount * 30) / 10000) as i128;
-    let platform_commission = (total_amount * platform_fee as i128) / 10000 as i128;
+    let total_amount = amount;
+    let trustless_work_commission = (total_amount * 30) / 10000;
+    let platform_commission = (total_amount * platform_fee as i128) / 10000_i128;
     let service_provider_amount =
-        (total_amount - (trustless_work_commission + platform_commission)) as i128;
+        total_amount - (trustless_work_commission + platform_commission);
 
     assert_eq!(
         usdc_token.0.balance(&trustless_work_address),
@@ -1099,7 +1757,6 @@ fn test_release_funds_invalid_receiver_fallback() {
     let platform_address = Address::generate(&env);
     let release_signer_address = Address::generate(&env);
     let dispute_resolver_address = Address::generate(&env);
-    let trustless_work_address = Address::generate(&env);
 
     // Create a valid but separate receiver address
     let _receiver_address = Address::generate(&env);
@@ -1107,7 +1764,7 @@ fn test_release_funds_invalid_receiver_fallback() {
     let usdc_token = create_usdc_token(&env, &admin);
 
     let amount: i128 = 100_000_000;
-    usdc_token.1.mint(&approver_address, &(amount as i128));
+    usdc_token.1.mint(&approver_address, &{ amount });
 
     let platform_fee = 5 * 100;
 
@@ -1115,9 +1772,16 @@ fn test_release_funds_invalid_receiver_fallback() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Completed"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -1134,6 +1798,7 @@ fn test_release_funds_invalid_receiver_fallback() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -1146,31 +1811,40 @@ fn test_release_funds_invalid_receiver_fallback() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles,
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: milestones.clone(),
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
     let escrow_approver = test_data.client;
+    let trustless_work_address = test_data.treasury;
 
     escrow_approver.initialize_escrow(&escrow_properties);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0]);
 
     usdc_token
         .1
-        .mint(&escrow_approver.address, &(amount as i128));
+        .mint(&escrow_approver.address, &{ amount });
 
     escrow_approver.approve_milestone(&0, &approver_address);
-    escrow_approver.release_funds(&release_signer_address, &trustless_work_address);
+    escrow_approver.release_funds(&release_signer_address);
 
-    let total_amount = amount as i128;
-    let trustless_work_commission = ((total_amount * 30) / 10000) as i128;
-    let platform_commission = (total_amount * platform_fee as i128) / 10000 as i128;
+    let total_amount = amount;
+    let trustless_work_commission = (total_amount * 30) / 10000;
+    let platform_commission = (total_amount * platform_fee as i128) / 10000_i128;
     let receiver_amount =
-        (total_amount - (trustless_work_commission + platform_commission)) as i128;
+        total_amount - (trustless_work_commission + platform_commission);
 
     assert_eq!(
         usdc_token.0.balance(&trustless_work_address),
@@ -1216,7 +1890,6 @@ fn test_dispute_management() {
     let platform_address = Address::generate(&env);
     let release_signer_address = Address::generate(&env);
     let dispute_resolver_address = Address::generate(&env);
-    let trustless_work_address = Address::generate(&env);
 
     let usdc_token = create_usdc_token(&env, &admin);
 
@@ -1228,9 +1901,16 @@ fn test_dispute_management() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -1247,6 +1927,7 @@ fn test_dispute_management() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -1258,12 +1939,19 @@ fn test_dispute_management() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles,
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: milestones.clone(),
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
@@ -1274,18 +1962,24 @@ fn test_dispute_management() {
     let escrow = escrow_approver.get_escrow();
     assert!(!escrow.flags.disputed);
 
-    escrow_approver.dispute_escrow(&approver_address);
+    escrow_approver.dispute_escrow(
+        &approver_address,
+        &String::from_str(&env, "Work not delivered"),
+    );
 
     let escrow_after_change = escrow_approver.get_escrow();
     assert!(escrow_after_change.flags.disputed);
 
-    usdc_token.1.mint(&approver_address, &(amount as i128));
+    usdc_token.1.mint(&approver_address, &{ amount });
     // Test block on distributing earnings during dispute
     let result =
-        escrow_approver.try_release_funds(&release_signer_address, &trustless_work_address);
+        escrow_approver.try_release_funds(&release_signer_address);
     assert!(result.is_err());
 
-    let _ = escrow_approver.try_dispute_escrow(&approver_address);
+    let _ = escrow_approver.try_dispute_escrow(
+        &approver_address,
+        &String::from_str(&env, "Work not delivered"),
+    );
 
     let escrow_after_second_change = escrow_approver.get_escrow();
     assert!(escrow_after_second_change.flags.disputed);
@@ -1302,12 +1996,11 @@ fn test_dispute_resolution_process() {
     let platform_address = Address::generate(&env);
     let release_signer_address = Address::generate(&env);
     let dispute_resolver_address = Address::generate(&env);
-    let trustless_work_address = Address::generate(&env);
 
     let usdc_token = create_usdc_token(&env, &admin);
 
     let amount: i128 = 100_000_000;
-    usdc_token.1.mint(&approver_address, &(amount as i128));
+    usdc_token.1.mint(&approver_address, &{ amount });
 
     let platform_fee = 5 * 100;
 
@@ -1315,9 +2008,16 @@ fn test_dispute_resolution_process() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Completed"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -1334,6 +2034,7 @@ fn test_dispute_resolution_process() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -1346,24 +2047,36 @@ fn test_dispute_resolution_process() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles,
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: milestones.clone(),
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
     let escrow_approver = test_data.client;
+    let trustless_work_address = test_data.treasury;
 
     escrow_approver.initialize_escrow(&escrow_properties);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0]);
 
     usdc_token
         .0
         .transfer(&approver_address, &escrow_approver.address, &amount);
 
-    escrow_approver.dispute_escrow(&approver_address);
+    escrow_approver.dispute_escrow(
+        &approver_address,
+        &String::from_str(&env, "Work not delivered"),
+    );
 
     let escrow_with_dispute = escrow_approver.get_escrow();
     assert!(escrow_with_dispute.flags.disputed);
@@ -1374,7 +2087,6 @@ fn test_dispute_resolution_process() {
     wrong_dist.set(service_provider_address.clone(), 50_000_000);
     let result = escrow_approver.try_resolve_dispute(
         &approver_address,
-        &trustless_work_address,
         &wrong_dist,
     );
     assert!(result.is_err());
@@ -1390,7 +2102,6 @@ fn test_dispute_resolution_process() {
     );
     let incorrect_dispute_resolution_result = escrow_approver.try_resolve_dispute(
         &dispute_resolver_address,
-        &trustless_work_address,
         &incorrect_dist,
     );
 
@@ -1399,7 +2110,6 @@ fn test_dispute_resolution_process() {
     let empty_dist = Map::new(&env);
     let dispute_resolution_with_incorrect_funds = escrow_approver.try_resolve_dispute(
         &dispute_resolver_address,
-        &trustless_work_address,
         &empty_dist,
     );
 
@@ -1411,16 +2121,16 @@ fn test_dispute_resolution_process() {
     let mut ok_dist = Map::new(&env);
     ok_dist.set(approver_address.clone(), approver_funds);
     ok_dist.set(service_provider_address.clone(), receiver_funds);
-    escrow_approver.resolve_dispute(&dispute_resolver_address, &trustless_work_address, &ok_dist);
+    escrow_approver.resolve_dispute(&dispute_resolver_address, &ok_dist);
 
     // Verify dispute was resolved
     let escrow_after_resolution = escrow_approver.get_escrow();
     assert!(!escrow_after_resolution.flags.disputed);
     assert!(escrow_after_resolution.flags.resolved);
 
-    let total_amount = amount as i128;
-    let trustless_work_commission = ((total_amount * 30) / 10000) as i128;
-    let platform_commission = (total_amount * platform_fee as i128) / 10000 as i128;
+    let total_amount = amount;
+    let trustless_work_commission = (total_amount * 30) / 10000;
+    let platform_commission = (total_amount * platform_fee as i128) / 10000_i128;
     let remaining_amount = total_amount - (trustless_work_commission + platform_commission);
 
     let platform_amount = platform_commission;
@@ -1477,9 +2187,16 @@ fn test_fund_escrow_successful_deposit() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -1496,6 +2213,7 @@ fn test_fund_escrow_successful_deposit() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -1508,12 +2226,19 @@ fn test_fund_escrow_successful_deposit() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles,
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: milestones.clone(),
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
@@ -1547,6 +2272,46 @@ fn test_fund_escrow_successful_deposit() {
 
     assert_eq!(usdc_token.0.balance(&approver_address), 0);
     assert_eq!(usdc_token.0.balance(&escrow_approver.address), amount);
+
+    // Both deposits are accumulated under the same depositor
+    assert_eq!(escrow_approver.get_deposit(&approver_address), amount);
+    let deposits = escrow_approver.get_deposits();
+    assert_eq!(deposits.len(), 1);
+    assert_eq!(deposits.get(0).unwrap().depositor, approver_address);
+    assert_eq!(deposits.get(0).unwrap().amount, amount);
+}
+
+#[test]
+fn test_fund_escrow_accepts_any_number_of_small_depositors() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+
+    for _ in 0..25 {
+        let depositor = Address::generate(&setup.env);
+        setup.token_admin.mint(&depositor, &1);
+        setup.client.fund_escrow(&depositor, &setup.escrow, &1);
+    }
+
+    assert_eq!(setup.client.get_deposits().len(), 25);
+    assert_eq!(setup.token.balance(&setup.client.address), 25);
+}
+
+#[test]
+fn test_fund_escrow_rejects_overfunding() {
+    let setup = EscrowSetup::new(&[6_000_000, 4_000_000]);
+    setup.initialize();
+    setup
+        .token_admin
+        .mint(&setup.approver, &(setup.escrow.amount + 1));
+
+    setup.client.fund_escrow(&setup.approver, &setup.escrow, &6_000_000);
+    let result = setup
+        .client
+        .try_fund_escrow(&setup.approver, &setup.escrow, &4_000_001);
+    assert_error(result, FundsError::DepositExceedsEscrowAmount);
+
+    setup.client.fund_escrow(&setup.approver, &setup.escrow, &4_000_000);
+    assert_eq!(setup.token.balance(&setup.client.address), setup.escrow.amount);
 }
 
 #[test]
@@ -1575,9 +2340,16 @@ fn test_fund_escrow_signer_insufficient_funds_error() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -1594,6 +2366,7 @@ fn test_fund_escrow_signer_insufficient_funds_error() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline: Trustline = Trustline {
@@ -1606,12 +2379,19 @@ fn test_fund_escrow_signer_insufficient_funds_error() {
         title: String::from_str(&env, "Test Escrow"),
         description: String::from_str(&env, "Test Escrow Description"),
         roles,
-        amount: amount,
-        platform_fee: platform_fee,
+        amount,
+        platform_fee,
         milestones: milestones.clone(),
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
@@ -1660,9 +2440,16 @@ fn test_dispute_escrow_authorized_and_unauthorized() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Completed"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: 10_000_000,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -1678,18 +2465,27 @@ fn test_dispute_escrow_authorized_and_unauthorized() {
             disputed: false,
             released: false,
             resolved: false,
+            cancelled: false,
         },
         trustline: Trustline {
             address: usdc_token.0.address.clone(),
         },
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     let test_data = create_escrow_contract(&env);
     let escrow_client_1 = test_data.client;
 
     escrow_client_1.initialize_escrow(&escrow_base);
-    escrow_client_1.dispute_escrow(&approver);
+    submit_milestones(&escrow_client_1, &service_provider, &[0]);
+    escrow_client_1.dispute_escrow(&approver, &String::from_str(&env, "Work not delivered"));
 
     let updated_escrow = escrow_client_1.get_escrow();
     assert!(
@@ -1701,7 +2497,11 @@ fn test_dispute_escrow_authorized_and_unauthorized() {
     let escrow_client_2 = test_data.client;
 
     escrow_client_2.initialize_escrow(&escrow_base);
-    let result = escrow_client_2.try_dispute_escrow(&unauthorized);
+    submit_milestones(&escrow_client_2, &service_provider, &[0]);
+    let result = escrow_client_2.try_dispute_escrow(
+        &unauthorized,
+        &String::from_str(&env, "Work not delivered"),
+    );
 
     assert!(
         result.is_err(),
@@ -1737,9 +2537,16 @@ fn test_get_multiple_escrow_balances_platform_authorized() {
         &env,
         Milestone {
             description: String::from_str(&env, "First milestone"),
-            status: String::from_str(&env, "Completed"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, "Initial evidence"),
+            amount: 50_000_000,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -1755,19 +2562,29 @@ fn test_get_multiple_escrow_balances_platform_authorized() {
             disputed: false,
             released: false,
             resolved: false,
+            cancelled: false,
         },
         trustline: Trustline {
             address: usdc_token.0.address.clone(),
         },
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     // Deploy two escrow contracts of the same code and initialize both
     let c1 = create_escrow_contract(&env).client;
     c1.initialize_escrow(&escrow_base);
+    submit_milestones(&c1, &service_provider, &[0]);
 
     let c2 = create_escrow_contract(&env).client;
     c2.initialize_escrow(&escrow_base);
+    submit_milestones(&c2, &service_provider, &[0]);
 
     // Mint funds to both contracts so they have balances
     usdc_token.1.mint(&c1.address, &escrow_base.amount);
@@ -1784,3 +2601,3715 @@ fn test_get_multiple_escrow_balances_platform_authorized() {
         c1.get_multiple_escrow_balances(&vec![&env, c1.address.clone(), c2.address.clone()]);
     assert_eq!(res_two.len(), 2);
 }
+
+#[test]
+fn test_release_milestone_funds_successful() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+    let receiver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let first_amount: i128 = 30_000_000;
+    let second_amount: i128 = 70_000_000;
+    let amount: i128 = first_amount + second_amount;
+    let platform_fee = 5 * 100;
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            description: String::from_str(&env, "First tranche"),
+            status: MilestoneStatus::Pending,
+            evidence: String::from_str(&env, "Initial evidence"),
+            amount: first_amount,
+            approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
+        },
+        Milestone {
+            description: String::from_str(&env, "Second tranche"),
+            status: MilestoneStatus::Pending,
+            evidence: String::from_str(&env, "Initial evidence"),
+            amount: second_amount,
+            approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
+        },
+    ];
+
+    let roles: Roles = Roles {
+        approver: approver_address.clone(),
+        service_provider: service_provider_address.clone(),
+        platform_address: platform_address.clone(),
+        release_signer: release_signer_address.clone(),
+        dispute_resolver: dispute_resolver_address.clone(),
+        receiver: receiver_address.clone(),
+    };
+
+    let flags: Flags = Flags {
+        disputed: false,
+        released: false,
+        resolved: false,
+        cancelled: false,
+    };
+
+    let trustline: Trustline = Trustline {
+        address: usdc_token.0.address.clone(),
+    };
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_milestone_release"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles,
+        amount,
+        platform_fee,
+        milestones,
+        flags,
+        trustline,
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+    let trustless_work_address = test_data.treasury;
+
+    escrow_approver.initialize_escrow(&escrow_properties);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0, 1]);
+    usdc_token.1.mint(&escrow_approver.address, &amount);
+
+    // Releasing a milestone that has not been approved must fail
+    let result = escrow_approver.try_release_milestone_funds(
+        &0,
+        &release_signer_address,
+    );
+    assert!(result.is_err());
+
+    escrow_approver.approve_milestone(&0, &approver_address);
+
+    // Only the release signer can release a milestone
+    let result = escrow_approver.try_release_milestone_funds(
+        &0,
+        &approver_address,
+    );
+    assert!(result.is_err());
+
+    escrow_approver.release_milestone_funds(&0, &release_signer_address);
+
+    let trustless_work_commission = (first_amount * 30) / 10000;
+    let platform_commission = (first_amount * platform_fee as i128) / 10000;
+    let receiver_amount = first_amount - (trustless_work_commission + platform_commission);
+
+    assert_eq!(
+        usdc_token.0.balance(&trustless_work_address),
+        trustless_work_commission
+    );
+    assert_eq!(usdc_token.0.balance(&platform_address), platform_commission);
+    assert_eq!(usdc_token.0.balance(&receiver_address), receiver_amount);
+    assert_eq!(
+        usdc_token.0.balance(&escrow_approver.address),
+        second_amount,
+        "The unreleased milestone amount must remain locked in the contract"
+    );
+
+    let escrow = escrow_approver.get_escrow();
+    assert!(escrow.milestones.get(0).unwrap().released);
+    assert!(!escrow.milestones.get(1).unwrap().released);
+    assert!(!escrow.flags.released);
+
+    // The same milestone cannot be released twice
+    let result = escrow_approver.try_release_milestone_funds(
+        &0,
+        &release_signer_address,
+    );
+    assert!(result.is_err());
+
+    escrow_approver.approve_milestone(&1, &approver_address);
+    escrow_approver.release_milestone_funds(&1, &release_signer_address);
+
+    let escrow = escrow_approver.get_escrow();
+    assert!(escrow.milestones.get(1).unwrap().released);
+    assert!(
+        escrow.flags.released,
+        "The escrow is released once every milestone has been released"
+    );
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 0);
+}
+
+#[test]
+fn test_release_funds_after_partial_milestone_release() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let amount: i128 = 100_000_000;
+    let platform_fee = 3 * 100;
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            description: String::from_str(&env, "First tranche"),
+            status: MilestoneStatus::Pending,
+            evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 4,
+            approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
+        },
+        Milestone {
+            description: String::from_str(&env, "Second tranche"),
+            status: MilestoneStatus::Pending,
+            evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount - amount / 4,
+            approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
+        },
+    ];
+
+    let roles: Roles = Roles {
+        approver: approver_address.clone(),
+        service_provider: service_provider_address.clone(),
+        platform_address: platform_address.clone(),
+        release_signer: release_signer_address.clone(),
+        dispute_resolver: dispute_resolver_address.clone(),
+        receiver: service_provider_address.clone(),
+    };
+
+    let flags: Flags = Flags {
+        disputed: false,
+        released: false,
+        resolved: false,
+        cancelled: false,
+    };
+
+    let trustline: Trustline = Trustline {
+        address: usdc_token.0.address.clone(),
+    };
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_partial_then_full_release"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles,
+        amount,
+        platform_fee,
+        milestones,
+        flags,
+        trustline,
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+
+    escrow_approver.initialize_escrow(&escrow_properties);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0, 1]);
+    usdc_token.1.mint(&escrow_approver.address, &amount);
+
+    escrow_approver.approve_milestone(&0, &approver_address);
+    escrow_approver.release_milestone_funds(&0, &release_signer_address);
+
+    escrow_approver.approve_milestone(&1, &approver_address);
+    escrow_approver.release_funds(&release_signer_address);
+
+    let escrow = escrow_approver.get_escrow();
+    assert!(escrow.flags.released);
+    assert!(escrow.milestones.iter().all(|m| m.released));
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 0);
+}
+
+#[test]
+fn test_initialize_escrow_rejects_milestone_amounts_mismatch() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let amount: i128 = 100_000_000;
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            description: String::from_str(&env, "First tranche"),
+            status: MilestoneStatus::Pending,
+            evidence: String::from_str(&env, "Initial evidence"),
+            amount: amount / 2,
+            approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
+        },
+    ];
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_amounts_mismatch"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee: 0,
+        milestones,
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let client = test_data.client;
+    let result = client.try_initialize_escrow(&escrow_properties);
+    assert_error(result, ContractError::MilestoneAmountsMustEqualEscrowAmount);
+}
+
+#[test]
+fn test_initialize_escrow_rejects_negative_milestone_amount() {
+    let mut setup = EscrowSetup::new(&[10_000_000, 10_000_000]);
+    setup.escrow.milestones.set(1, create_milestone(&setup.env, -10_000_000));
+    setup.escrow.amount = 0;
+
+    let result = setup.client.try_initialize_escrow(&setup.escrow);
+    assert_error(result, ContractError::AmountCannotBeNegative);
+}
+
+#[test]
+fn test_initialize_escrow_rejects_zero_milestone_amount() {
+    let mut setup = EscrowSetup::new(&[10_000_000, 10_000_000]);
+    setup.escrow.milestones.set(1, create_milestone(&setup.env, 0));
+    setup.escrow.amount = 10_000_000;
+
+    let result = setup.client.try_initialize_escrow(&setup.escrow);
+    assert_error(result, ContractError::AmountCannotBeZero);
+}
+
+#[test]
+fn test_initialize_escrow_requires_pending_milestones() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    let pending = setup.escrow.milestones.get(0).unwrap();
+
+    let mut submitted = pending.clone();
+    submitted.status = MilestoneStatus::Submitted;
+    let mut rejected = pending.clone();
+    rejected.rejection_reason = Some(String::from_str(&setup.env, "Incomplete"));
+    let mut resubmitted = pending;
+    resubmitted.rejection_count = 1;
+
+    for milestone in [submitted, rejected, resubmitted] {
+        let mut escrow = setup.escrow.clone();
+        escrow.milestones.set(0, milestone);
+        let result = setup.client.try_initialize_escrow(&escrow);
+        assert_error(result, ContractError::FlagsMustBeFalse);
+    }
+}
+
+#[test]
+fn test_update_requires_new_and_changed_milestones_to_be_pending() {
+    let setup = EscrowSetup::new(&[5_000_000, 5_000_000]);
+    setup.initialize();
+    setup.submit(0);
+
+    let mut updated_properties = setup.client.get_escrow();
+    let mut changed = updated_properties.milestones.get(1).unwrap();
+    changed.description = String::from_str(&setup.env, "Changed milestone");
+    changed.status = MilestoneStatus::InProgress;
+    updated_properties.milestones.set(1, changed.clone());
+    let result = setup
+        .client
+        .try_update_escrow(&setup.platform, &updated_properties);
+    assert_error(result, ContractError::FlagsMustBeFalse);
+
+    // The submitted milestone is left as it is, so only the changed one has to be pending
+    changed.status = MilestoneStatus::Pending;
+    updated_properties.milestones.set(1, changed);
+    setup
+        .client
+        .update_escrow(&setup.platform, &updated_properties);
+    assert_eq!(setup.milestone(0).status, MilestoneStatus::Submitted);
+    assert_eq!(
+        setup.milestone(1).description,
+        String::from_str(&setup.env, "Changed milestone")
+    );
+}
+
+#[test]
+fn test_refund_after_deadline() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let amount: i128 = 100_000_000;
+    usdc_token.1.mint(&approver_address, &amount);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            description: String::from_str(&env, "First milestone"),
+            status: MilestoneStatus::Pending,
+            evidence: String::from_str(&env, "Initial evidence"),
+            amount,
+            approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
+        },
+    ];
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_refund_deadline"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee: 0,
+        milestones,
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::Timestamp(2_000),
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+
+    escrow_approver.initialize_escrow(&escrow_properties);
+    escrow_approver.fund_escrow(&approver_address, &escrow_properties, &(amount / 2));
+
+    let result = escrow_approver.try_refund();
+    assert_error(result, FundsError::EscrowDeadlineNotReached);
+
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+
+    let result = escrow_approver.try_fund_escrow(&approver_address, &escrow_properties, &(amount / 2));
+    assert_error(result, FundsError::EscrowDeadlinePassed);
+
+    escrow_approver.refund();
+
+    assert_eq!(usdc_token.0.balance(&approver_address), amount);
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 0);
+
+    let result = escrow_approver.try_refund();
+    assert_error(result, FundsError::EscrowBalanceIsZero);
+}
+
+#[test]
+fn test_refund_blocked_by_approved_milestone() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let amount: i128 = 100_000_000;
+    usdc_token.1.mint(&approver_address, &amount);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            description: String::from_str(&env, "First milestone"),
+            status: MilestoneStatus::Pending,
+            evidence: String::from_str(&env, "Initial evidence"),
+            amount,
+            approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
+        },
+    ];
+
+    let deadline_ledger = env.ledger().sequence() + 100;
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_refund_approved"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee: 0,
+        milestones,
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::Ledger(deadline_ledger),
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+
+    escrow_approver.initialize_escrow(&escrow_properties);
+    escrow_approver.fund_escrow(&approver_address, &escrow_properties, &amount);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0]);
+    escrow_approver.approve_milestone(&0, &approver_address);
+
+    env.ledger().with_mut(|li| li.sequence_number = deadline_ledger);
+
+    let result = escrow_approver.try_refund();
+    assert_error(result, FundsError::EscrowHasApprovedMilestones);
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), amount);
+}
+
+#[test]
+fn test_initialize_escrow_rejects_past_deadline() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 5_000);
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_past_deadline"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: approver_address.clone(),
+            dispute_resolver: platform_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount: 1_000,
+        platform_fee: 0,
+        milestones: vec![
+            &env,
+            Milestone {
+                description: String::from_str(&env, "First milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, "Initial evidence"),
+                amount: 1_000,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+        ],
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::Timestamp(4_000),
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let result = test_data.client.try_initialize_escrow(&escrow_properties);
+    assert_error(result, FundsError::InvalidEscrowDeadline);
+}
+
+#[test]
+fn test_refund_pro_rata_to_depositors() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let investor_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let amount: i128 = 100_000_000;
+    let approver_deposit: i128 = 30_000_000;
+    let investor_deposit: i128 = 70_000_000;
+    usdc_token.1.mint(&approver_address, &approver_deposit);
+    usdc_token.1.mint(&investor_address, &investor_deposit);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            description: String::from_str(&env, "First milestone"),
+            status: MilestoneStatus::Pending,
+            evidence: String::from_str(&env, "Initial evidence"),
+            amount,
+            approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
+        },
+    ];
+
+    let deadline_ledger = env.ledger().sequence() + 10;
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_refund_pro_rata"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee: 0,
+        milestones,
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::Ledger(deadline_ledger),
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+
+    escrow_approver.initialize_escrow(&escrow_properties);
+    escrow_approver.fund_escrow(&approver_address, &escrow_properties, &approver_deposit);
+    escrow_approver.fund_escrow(&investor_address, &escrow_properties, &investor_deposit);
+
+    assert_eq!(escrow_approver.get_deposit(&approver_address), approver_deposit);
+    assert_eq!(escrow_approver.get_deposit(&investor_address), investor_deposit);
+    assert_eq!(escrow_approver.get_deposits().len(), 2);
+
+    // A stray transfer that did not go through fund_escrow is shared pro rata as well
+    usdc_token.1.mint(&escrow_approver.address, &7);
+
+    env.ledger().with_mut(|li| li.sequence_number = deadline_ledger);
+    escrow_approver.refund();
+
+    let refundable = amount + 7;
+    let approver_refund = (refundable * approver_deposit) / amount;
+    assert_eq!(usdc_token.0.balance(&approver_address), approver_refund);
+    assert_eq!(
+        usdc_token.0.balance(&investor_address),
+        refundable - approver_refund,
+        "The last depositor receives the rounding remainder"
+    );
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 0);
+    assert!(escrow_approver.get_deposits().is_empty());
+}
+
+#[test]
+fn test_milestone_status_transitions_are_enforced() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let amount: i128 = 100_000_000;
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_status_transitions"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee: 0,
+        milestones: vec![
+            &env,
+            Milestone {
+                description: String::from_str(&env, "First milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, "Initial evidence"),
+                amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+        ],
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+    escrow_approver.initialize_escrow(&escrow_properties);
+
+    // A pending milestone cannot be approved
+    let result = escrow_approver.try_approve_milestone(&0, &approver_address);
+    assert_error(result, ContractError::InvalidMilestoneStatusTransition);
+
+    // The service provider cannot mark a milestone as approved or released
+    for status in [MilestoneStatus::Approved, MilestoneStatus::Released] {
+        let result = escrow_approver.try_change_milestone_status(
+            &0,
+            &status,
+            &None,
+            &service_provider_address,
+        );
+        assert_error(result, ContractError::InvalidMilestoneStatusTransition);
+    }
+
+    escrow_approver.change_milestone_status(
+        &0,
+        &MilestoneStatus::InProgress,
+        &None,
+        &service_provider_address,
+    );
+    escrow_approver.change_milestone_status(
+        &0,
+        &MilestoneStatus::Submitted,
+        &Some(create_evidence(&env, "ipfs://evidence", 1)),
+        &service_provider_address,
+    );
+
+    // A submitted milestone cannot be sent back to pending
+    let result = escrow_approver.try_change_milestone_status(
+        &0,
+        &MilestoneStatus::Pending,
+        &None,
+        &service_provider_address,
+    );
+    assert_error(result, ContractError::InvalidMilestoneStatusTransition);
+
+    escrow_approver.approve_milestone(&0, &approver_address);
+    let milestone = escrow_approver.get_escrow().milestones.get(0).unwrap();
+    assert_eq!(milestone.status, MilestoneStatus::Approved);
+    assert!(milestone.approved);
+
+    // Once approved the service provider can no longer move the milestone
+    let result = escrow_approver.try_change_milestone_status(
+        &0,
+        &MilestoneStatus::InProgress,
+        &None,
+        &service_provider_address,
+    );
+    assert_error(result, ContractError::InvalidMilestoneStatusTransition);
+
+    usdc_token.1.mint(&escrow_approver.address, &amount);
+    escrow_approver.release_milestone_funds(&0, &release_signer_address);
+    let milestone = escrow_approver.get_escrow().milestones.get(0).unwrap();
+    assert_eq!(milestone.status, MilestoneStatus::Released);
+}
+
+#[test]
+fn test_reject_milestone_sends_it_back_to_the_provider() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let amount: i128 = 100_000_000;
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_reject_milestone"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee: 0,
+        milestones: vec![
+            &env,
+            Milestone {
+                description: String::from_str(&env, "First milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, "Initial evidence"),
+                amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+        ],
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+    escrow_approver.initialize_escrow(&escrow_properties);
+
+    let reason = String::from_str(&env, "Photos do not show the finished roof");
+
+    // Only submitted milestones can be rejected
+    let result = escrow_approver.try_reject_milestone(&0, &approver_address, &reason);
+    assert_error(result, ContractError::InvalidMilestoneStatusTransition);
+
+    escrow_approver.change_milestone_status(
+        &0,
+        &MilestoneStatus::Submitted,
+        &Some(create_evidence(&env, "ipfs://roof-photos", 2)),
+        &service_provider_address,
+    );
+
+    let result =
+        escrow_approver.try_reject_milestone(&0, &service_provider_address, &reason);
+    assert_error(result, ContractError::OnlyApproverChangeMilstoneFlag);
+
+    let result = escrow_approver.try_reject_milestone(
+        &0,
+        &approver_address,
+        &String::from_str(&env, ""),
+    );
+    assert_error(result, ContractError::EmptyRejectionReason);
+
+    escrow_approver.reject_milestone(&0, &approver_address, &reason);
+
+    let milestone = escrow_approver.get_escrow().milestones.get(0).unwrap();
+    assert_eq!(milestone.status, MilestoneStatus::Rejected);
+    assert_eq!(milestone.rejection_reason, Some(reason.clone()));
+    assert_eq!(milestone.rejection_count, 1);
+    assert!(!milestone.approved);
+
+    // A rejected milestone cannot be approved until it is submitted again
+    let result = escrow_approver.try_approve_milestone(&0, &approver_address);
+    assert!(result.is_err());
+
+    escrow_approver.change_milestone_status(
+        &0,
+        &MilestoneStatus::Submitted,
+        &Some(create_evidence(&env, "ipfs://roof-photos-v2", 3)),
+        &service_provider_address,
+    );
+    escrow_approver.reject_milestone(&0, &approver_address, &reason);
+    assert_eq!(
+        escrow_approver.get_escrow().milestones.get(0).unwrap().rejection_count,
+        2
+    );
+
+    escrow_approver.change_milestone_status(
+        &0,
+        &MilestoneStatus::Submitted,
+        &Some(create_evidence(&env, "ipfs://roof-photos-v3", 4)),
+        &service_provider_address,
+    );
+    escrow_approver.approve_milestone(&0, &approver_address);
+
+    let milestone = escrow_approver.get_escrow().milestones.get(0).unwrap();
+    assert_eq!(milestone.status, MilestoneStatus::Approved);
+    assert_eq!(milestone.rejection_count, 2);
+}
+
+#[test]
+fn test_dispute_and_resolve_single_milestone() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let first_amount: i128 = 60_000_000;
+    let second_amount: i128 = 40_000_000;
+    let amount = first_amount + second_amount;
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_milestone_dispute"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee: 0,
+        milestones: vec![
+            &env,
+            Milestone {
+                description: String::from_str(&env, "First milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, "ipfs://first"),
+                amount: first_amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+            Milestone {
+                description: String::from_str(&env, "Second milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, "ipfs://second"),
+                amount: second_amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+        ],
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+    let trustless_work_address = test_data.treasury;
+    escrow_approver.initialize_escrow(&escrow_properties);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0, 1]);
+    usdc_token.1.mint(&escrow_approver.address, &amount);
+
+    // The dispute resolver cannot open a dispute on a milestone
+    let result = escrow_approver.try_dispute_milestone(&0, &dispute_resolver_address);
+    assert_error(result, ContractError::DisputeResolverCannotDisputeTheEscrow);
+
+    escrow_approver.dispute_milestone(&0, &approver_address);
+
+    let escrow = escrow_approver.get_escrow();
+    assert!(escrow.milestones.get(0).unwrap().disputed);
+    assert!(!escrow.milestones.get(1).unwrap().disputed);
+    assert!(!escrow.flags.disputed);
+
+    let result = escrow_approver.try_dispute_milestone(&0, &service_provider_address);
+    assert_error(result, DisputeError::MilestoneAlreadyInDispute);
+
+    let result = escrow_approver.try_approve_milestone(&0, &approver_address);
+    assert_error(result, DisputeError::MilestoneOpenedForDisputeResolution);
+
+    // The other milestone keeps moving while the first one is in dispute
+    escrow_approver.approve_milestone(&1, &approver_address);
+    escrow_approver.release_milestone_funds(&1, &release_signer_address);
+
+    let second_fee = (second_amount * 30) / 10000;
+    assert_eq!(
+        usdc_token.0.balance(&service_provider_address),
+        second_amount - second_fee
+    );
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), first_amount);
+
+    // Distributions must cover exactly the disputed milestone's amount
+    let mut distributions: Map<Address, i128> = Map::new(&env);
+    distributions.set(approver_address.clone(), amount / 2);
+    distributions.set(service_provider_address.clone(), amount / 2);
+    let result = escrow_approver.try_resolve_milestone_dispute(
+        &0,
+        &dispute_resolver_address,
+        &distributions,
+    );
+    assert_error(result, DisputeError::DistributionsMustEqualMilestoneAmount);
+
+    let mut distributions: Map<Address, i128> = Map::new(&env);
+    distributions.set(approver_address.clone(), first_amount / 2);
+    distributions.set(service_provider_address.clone(), first_amount / 2);
+    escrow_approver.resolve_milestone_dispute(
+        &0,
+        &dispute_resolver_address,
+        &distributions,
+    );
+
+    let first_fee = (first_amount * 30) / 10000;
+    let share_fee = first_fee / 2;
+    assert_eq!(
+        usdc_token.0.balance(&approver_address),
+        first_amount / 2 - share_fee
+    );
+    assert_eq!(
+        usdc_token.0.balance(&service_provider_address),
+        second_amount - second_fee + first_amount / 2 - share_fee
+    );
+    assert_eq!(
+        usdc_token.0.balance(&trustless_work_address),
+        first_fee + second_fee
+    );
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 0);
+
+    let escrow = escrow_approver.get_escrow();
+    let milestone = escrow.milestones.get(0).unwrap();
+    assert!(!milestone.disputed);
+    assert!(milestone.resolved);
+    assert!(
+        escrow.flags.released,
+        "Every milestone has been either released or resolved"
+    );
+
+    let result = escrow_approver.try_dispute_milestone(&0, &approver_address);
+    assert_error(result, ContractError::EscrowAlreadyReleased);
+}
+
+#[test]
+fn test_milestone_evidence_history_is_append_only() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let amount: i128 = 100_000_000;
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_evidence_history"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee: 0,
+        milestones: vec![
+            &env,
+            Milestone {
+                description: String::from_str(&env, "First milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, ""),
+                amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+        ],
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+    escrow_approver.initialize_escrow(&escrow_properties);
+
+    assert_eq!(escrow_approver.get_milestone_evidence(&0, &0, &10).len(), 0);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    escrow_approver.change_milestone_status(
+        &0,
+        &MilestoneStatus::Submitted,
+        &Some(create_evidence(&env, "ipfs://draft", 1)),
+        &service_provider_address,
+    );
+    escrow_approver.reject_milestone(
+        &0,
+        &approver_address,
+        &String::from_str(&env, "Draft is incomplete"),
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    escrow_approver.change_milestone_status(
+        &0,
+        &MilestoneStatus::Submitted,
+        &Some(create_evidence(&env, "ipfs://final", 2)),
+        &service_provider_address,
+    );
+
+    // A status change without evidence does not add an entry
+    escrow_approver.change_milestone_status(
+        &0,
+        &MilestoneStatus::Submitted,
+        &None,
+        &service_provider_address,
+    );
+
+    let history = escrow_approver.get_milestone_evidence(&0, &0, &10);
+    assert_eq!(history.len(), 2);
+
+    let first = history.get(0).unwrap();
+    assert_eq!(first.submitter, service_provider_address);
+    assert_eq!(first.timestamp, 1_000);
+    assert_eq!(first.uri, String::from_str(&env, "ipfs://draft"));
+    assert_eq!(first.hash, BytesN::from_array(&env, &[1; 32]));
+
+    let second = history.get(1).unwrap();
+    assert_eq!(second.timestamp, 2_000);
+    assert_eq!(second.uri, String::from_str(&env, "ipfs://final"));
+    assert_eq!(second.hash, BytesN::from_array(&env, &[2; 32]));
+
+    // The milestone keeps pointing at the latest submission
+    assert_eq!(
+        escrow_approver.get_escrow().milestones.get(0).unwrap().evidence,
+        String::from_str(&env, "ipfs://final")
+    );
+
+    let page = escrow_approver.get_milestone_evidence(&0, &1, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap(), second);
+    assert_eq!(escrow_approver.get_milestone_evidence(&0, &2, &5).len(), 0);
+
+    let result = escrow_approver.try_get_milestone_evidence(&1, &0, &10);
+    assert_error(result, ContractError::InvalidMileStoneIndex);
+}
+
+/// The escrow as the code deployed before storage was split wrote it, with the roles and
+/// token of `setup` and one milestone per `(description, approved)` pair.
+fn create_legacy_escrow(
+    setup: &EscrowSetup,
+    amount: i128,
+    milestones: &[(&str, bool)],
+) -> LegacyEscrow {
+    let env = &setup.env;
+    let mut legacy_milestones = vec![env];
+    for (description, approved) in milestones {
+        legacy_milestones.push_back(LegacyMilestone {
+            description: String::from_str(env, description),
+            status: String::from_str(env, if *approved { "approved" } else { "pending" }),
+            evidence: String::from_str(env, ""),
+            approved: *approved,
+        });
+    }
+
+    LegacyEscrow {
+        engagement_id: String::from_str(env, "test_legacy_escrow"),
+        title: String::from_str(env, "Test Escrow"),
+        roles: LegacyRoles {
+            approver: setup.approver.clone(),
+            service_provider: setup.service_provider.clone(),
+            platform_address: setup.platform.clone(),
+            release_signer: setup.release_signer.clone(),
+            dispute_resolver: setup.dispute_resolver.clone(),
+            receiver: setup.service_provider.clone(),
+        },
+        description: String::from_str(env, "Test Escrow Description"),
+        amount,
+        platform_fee: 0,
+        milestones: legacy_milestones,
+        flags: LegacyFlags {
+            disputed: false,
+            released: false,
+            resolved: false,
+        },
+        trustline: LegacyTrustline {
+            address: setup.token.address.clone(),
+        },
+        receiver_memo: 0,
+    }
+}
+
+/// Writes `legacy` the way the code deployed before storage was split did.
+fn store_legacy_escrow(setup: &EscrowSetup, legacy: &LegacyEscrow) {
+    setup.env.as_contract(&setup.client.address, || {
+        setup.env.storage().instance().set(&DataKey::Escrow, legacy);
+    });
+}
+
+#[test]
+fn test_legacy_escrow_converts_to_the_current_layout() {
+    let setup = EscrowSetup::new(&[]);
+    let legacy = create_legacy_escrow(
+        &setup,
+        10_000_001,
+        &[("Design", false), ("Build", true), ("Launch", false)],
+    );
+    store_legacy_escrow(&setup, &legacy);
+
+    let escrow = setup.client.get_escrow();
+    assert!(escrow.roles == setup.escrow.roles);
+    assert_eq!(escrow.amount, 10_000_001);
+    assert!(!escrow.flags.cancelled);
+    assert_eq!(escrow.deadline, Deadline::None);
+    assert!(escrow.payees.is_empty());
+    assert!(escrow.co_approvers.is_empty());
+    assert_eq!(escrow.approval_threshold, 1);
+    assert_eq!(escrow.arbitration, Arbitration::None);
+    assert_eq!(escrow.appeal, Appeal::None);
+    assert_eq!(escrow.review_period, None);
+
+    // The amount is split evenly and the last milestone takes the remainder
+    let amounts: std::vec::Vec<i128> = escrow.milestones.iter().map(|m| m.amount).collect();
+    assert_eq!(amounts, std::vec![3_333_333, 3_333_333, 3_333_335]);
+
+    let build = escrow.milestones.get(1).unwrap();
+    assert_eq!(build.description, String::from_str(&setup.env, "Build"));
+    assert_eq!(build.status, MilestoneStatus::Approved);
+    assert!(build.approved);
+    assert_eq!(
+        escrow.milestones.get(0).unwrap().status,
+        MilestoneStatus::Pending
+    );
+}
+
+#[test]
+fn test_migrate_legacy_escrow_storage() {
+    let setup = EscrowSetup::new(&[]);
+    let legacy = create_legacy_escrow(
+        &setup,
+        100_000_000,
+        &[("First milestone", false), ("Second milestone", false)],
+    );
+    store_legacy_escrow(&setup, &legacy);
+    let converted = setup.client.get_escrow();
+
+    setup.client.migrate(&setup.platform);
+
+    setup.env.as_contract(&setup.client.address, || {
+        let env = &setup.env;
+        assert!(!env.storage().instance().has(&DataKey::Escrow));
+        assert!(env.storage().instance().has(&DataKey::Header));
+        assert!(env.storage().persistent().has(&DataKey::Milestone(0)));
+        assert!(env.storage().persistent().has(&DataKey::Milestone(1)));
+        assert_eq!(
+            env.storage().instance().get::<_, u32>(&DataKey::Version),
+            Some(2)
+        );
+    });
+    assert!(setup.client.get_escrow() == converted);
+
+    let result = setup.client.try_migrate(&setup.platform);
+    assert_error(result, ContractError::StorageAlreadyMigrated);
+
+    // Milestone updates only touch the milestone's own entry
+    setup.client.change_milestone_status(
+        &1,
+        &MilestoneStatus::InProgress,
+        &None,
+        &setup.service_provider,
+    );
+    assert_eq!(setup.milestone(1).status, MilestoneStatus::InProgress);
+    assert_eq!(setup.milestone(0).status, MilestoneStatus::Pending);
+
+    // Dropping a milestone while unfunded removes its entry
+    let mut updated_properties = setup.client.get_escrow();
+    updated_properties.amount = 50_000_000;
+    updated_properties.milestones = vec![&setup.env, setup.milestone(0)];
+    setup
+        .client
+        .update_escrow(&setup.platform, &updated_properties);
+
+    setup.env.as_contract(&setup.client.address, || {
+        assert!(setup.env.storage().persistent().has(&DataKey::Milestone(0)));
+        assert!(!setup.env.storage().persistent().has(&DataKey::Milestone(1)));
+    });
+    assert_eq!(setup.client.get_escrow().milestones.len(), 1);
+}
+
+#[test]
+fn test_evidence_log_is_bounded() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+
+    let submit_with_evidence = |seed: u8| {
+        setup.client.try_change_milestone_status(
+            &0,
+            &MilestoneStatus::Submitted,
+            &Some(create_evidence(&setup.env, "ipfs://evidence", seed)),
+            &setup.service_provider,
+        )
+    };
+    for seed in 0..20 {
+        assert!(submit_with_evidence(seed).is_ok());
+    }
+
+    let result = submit_with_evidence(20);
+    assert_error(result, WorkflowError::TooManyEvidenceEntries);
+    assert_eq!(setup.client.get_milestone_evidence(&0, &0, &50).len(), 20);
+}
+
+#[test]
+fn test_extend_milestone_ttl() {
+    let setup = EscrowSetup::new(&[5_000_000, 5_000_000]);
+    setup.initialize();
+    setup.client.change_milestone_status(
+        &1,
+        &MilestoneStatus::Submitted,
+        &Some(create_evidence(&setup.env, "ipfs://evidence", 1)),
+        &setup.service_provider,
+    );
+
+    setup.client.extend_milestone_ttl(&setup.platform, &1, &10_000);
+    setup.env.as_contract(&setup.client.address, || {
+        let storage = setup.env.storage().persistent();
+        assert_eq!(storage.get_ttl(&DataKey::Milestone(1)), 10_000);
+        assert_eq!(storage.get_ttl(&DataKey::Evidence(1, 0, 0)), 10_000);
+        assert!(storage.get_ttl(&DataKey::Milestone(0)) < 10_000);
+    });
+
+    let result = setup
+        .client
+        .try_extend_milestone_ttl(&setup.platform, &2, &10_000);
+    assert_error(result, ContractError::InvalidMileStoneIndex);
+
+    let result = setup
+        .client
+        .try_extend_milestone_ttl(&setup.approver, &0, &10_000);
+    assert_error(result, ContractError::OnlyPlatformAddressExecuteThisFunction);
+}
+
+#[test]
+fn test_milestone_index_must_point_at_an_existing_milestone() {
+    let setup = EscrowSetup::new(&[5_000_000, 5_000_000]);
+    setup.initialize_and_fund();
+    let distributions = create_distributions(&setup.env, &[(&setup.service_provider, 5_000_000)]);
+
+    // Negative indexes and indexes that would wrap around when truncated to `u32`
+    for index in [-1, 2, u32::MAX as i128 + 1] {
+        let result = setup
+            .client
+            .try_release_milestone_funds(&index, &setup.release_signer);
+        assert_error(result, ContractError::InvalidMileStoneIndex);
+
+        let result = setup.client.try_dispute_milestone(&index, &setup.approver);
+        assert_error(result, ContractError::InvalidMileStoneIndex);
+
+        let result = setup.client.try_vote_milestone_resolution(
+            &index,
+            &setup.dispute_resolver,
+            &distributions,
+        );
+        assert_error(result, ContractError::InvalidMileStoneIndex);
+    }
+}
+
+#[test]
+fn test_upgrade_and_post_upgrade_migration() {
+    let setup = EscrowSetup::new(&[]);
+    assert_eq!(setup.client.version(), 2);
+
+    // An escrow from before versioning: legacy layout and no stored version
+    let legacy = create_legacy_escrow(&setup, 100_000_000, &[("First milestone", false)]);
+    store_legacy_escrow(&setup, &legacy);
+    let converted = setup.client.get_escrow();
+
+    let new_wasm_hash = BytesN::from_array(&setup.env, &[7; 32]);
+    let result = setup.client.try_upgrade(&setup.approver, &new_wasm_hash);
+    assert_error(result, ContractError::OnlyPlatformAddressExecuteThisFunction);
+
+    let result = setup.client.try_migrate(&setup.service_provider);
+    assert_error(result, ContractError::OnlyPlatformAddressExecuteThisFunction);
+
+    setup.client.migrate(&setup.platform);
+
+    setup.env.as_contract(&setup.client.address, || {
+        let env = &setup.env;
+        assert!(!env.storage().instance().has(&DataKey::Escrow));
+        assert_eq!(
+            env.storage().instance().get::<_, u32>(&DataKey::Version),
+            Some(2)
+        );
+    });
+    assert!(setup.client.get_escrow() == converted);
+
+    let result = setup.client.try_migrate(&setup.platform);
+    assert_error(result, ContractError::StorageAlreadyMigrated);
+}
+
+#[test]
+fn test_upgrade_requires_the_protocol_admin() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+    let new_wasm_hash = BytesN::from_array(&setup.env, &[7; 32]);
+
+    // Once every signature is present, the platform address is checked
+    let result = setup.client.try_upgrade(&setup.approver, &new_wasm_hash);
+    assert_error(result, ContractError::OnlyPlatformAddressExecuteThisFunction);
+
+    // Without the protocol admin's signature the call is rejected before that
+    setup.env.mock_auths(&[MockAuth {
+        address: &setup.approver,
+        invoke: &MockAuthInvoke {
+            contract: &setup.client.address,
+            fn_name: "upgrade",
+            args: (&setup.approver, &new_wasm_hash).into_val(&setup.env),
+            sub_invokes: &[],
+        },
+    }]);
+    let result = setup.client.try_upgrade(&setup.approver, &new_wasm_hash);
+    assert!(matches!(result, Err(Ok(error)) if !error.is_type(ScErrorType::Contract)));
+}
+
+#[test]
+fn test_protocol_fee_and_treasury_come_from_the_protocol_config() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let first_amount: i128 = 40_000_000;
+    let second_amount: i128 = 60_000_000;
+    let amount = first_amount + second_amount;
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_protocol_config"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee: 0,
+        milestones: vec![
+            &env,
+            Milestone {
+                description: String::from_str(&env, "First milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, ""),
+                amount: first_amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+            Milestone {
+                description: String::from_str(&env, "Second milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, ""),
+                amount: second_amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+        ],
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+    let protocol_admin = test_data.admin;
+    let original_treasury = test_data.treasury;
+
+    // The constructor sets the treasury with the default protocol fee
+    assert_eq!(
+        escrow_approver.get_protocol_config(),
+        ProtocolConfig {
+            fee_bps: DEFAULT_PROTOCOL_FEE_BPS,
+            treasury: original_treasury.clone(),
+        }
+    );
+
+    let new_treasury = Address::generate(&env);
+    let new_config = ProtocolConfig {
+        fee_bps: MAX_PROTOCOL_FEE_BPS,
+        treasury: new_treasury.clone(),
+    };
+
+    let result = escrow_approver.try_set_protocol_config(&platform_address, &new_config);
+    assert_error(result, FundsError::OnlyAdminCanExecuteThisFunction);
+
+    let result = escrow_approver.try_set_protocol_config(
+        &protocol_admin,
+        &ProtocolConfig {
+            fee_bps: MAX_PROTOCOL_FEE_BPS + 1,
+            treasury: new_treasury.clone(),
+        },
+    );
+    assert_error(result, FundsError::InvalidProtocolFee);
+
+    escrow_approver.set_protocol_config(&protocol_admin, &new_config);
+    assert_eq!(last_signers(&env), std::vec![protocol_admin.clone()]);
+    assert_eq!(escrow_approver.get_protocol_config(), new_config);
+
+    escrow_approver.initialize_escrow(&escrow_properties);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0, 1]);
+    usdc_token.1.mint(&escrow_approver.address, &amount);
+
+    // Once initialized, the escrow keeps its terms
+    let result = escrow_approver.try_set_protocol_config(
+        &protocol_admin,
+        &ProtocolConfig {
+            fee_bps: 0,
+            treasury: original_treasury.clone(),
+        },
+    );
+    assert_error(result, ContractError::EscrowAlreadyInitialized);
+    assert_eq!(escrow_approver.get_protocol_config(), new_config);
+
+    escrow_approver.approve_milestone(&0, &approver_address);
+    escrow_approver.release_milestone_funds(&0, &release_signer_address);
+
+    let first_fee = (first_amount * 100) / 10000;
+    assert_eq!(usdc_token.0.balance(&new_treasury), first_fee);
+    assert_eq!(usdc_token.0.balance(&original_treasury), 0);
+    assert_eq!(
+        usdc_token.0.balance(&service_provider_address),
+        first_amount - first_fee
+    );
+
+    escrow_approver.approve_milestone(&1, &approver_address);
+    escrow_approver.release_milestone_funds(&1, &release_signer_address);
+
+    let second_fee = (second_amount * 100) / 10000;
+    assert_eq!(usdc_token.0.balance(&new_treasury), first_fee + second_fee);
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 0);
+}
+
+#[test]
+fn test_release_and_dispute_split_between_payees() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+    let receiver_address = Address::generate(&env);
+    let contractor = Address::generate(&env);
+    let subcontractor = Address::generate(&env);
+    let spv = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let first_amount: i128 = 1_000_001;
+    let second_amount: i128 = 2_000_000;
+    let amount = first_amount + second_amount;
+
+    let mut escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_payees"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: receiver_address.clone(),
+        },
+        amount,
+        platform_fee: 0,
+        milestones: vec![
+            &env,
+            Milestone {
+                description: String::from_str(&env, "First milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, ""),
+                amount: first_amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+            Milestone {
+                description: String::from_str(&env, "Second milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, ""),
+                amount: second_amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+        ],
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![
+            &env,
+            Payee {
+                address: contractor.clone(),
+                share_bps: 5_000,
+            },
+            Payee {
+                address: subcontractor.clone(),
+                share_bps: 3_333,
+            },
+            Payee {
+                address: spv.clone(),
+                share_bps: 1_000,
+            },
+        ],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+
+    let result = escrow_approver.try_initialize_escrow(&escrow_properties);
+    assert_error(result, FundsError::PayeeSharesMustSumToTotal);
+
+    escrow_properties.payees.set(
+        2,
+        Payee {
+            address: contractor.clone(),
+            share_bps: 1_667,
+        },
+    );
+    let result = escrow_approver.try_initialize_escrow(&escrow_properties);
+    assert_error(result, FundsError::DuplicatePayee);
+
+    escrow_properties.payees.set(
+        2,
+        Payee {
+            address: spv.clone(),
+            share_bps: 1_667,
+        },
+    );
+    escrow_approver.initialize_escrow(&escrow_properties);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0, 1]);
+    usdc_token.1.mint(&escrow_approver.address, &amount);
+
+    escrow_approver.approve_milestone(&0, &approver_address);
+    escrow_approver.release_milestone_funds(&0, &release_signer_address);
+
+    let first_fee = (first_amount * 30) / 10000;
+    let first_net = first_amount - first_fee;
+    let contractor_share = (first_net * 5_000) / 10000;
+    let subcontractor_share = (first_net * 3_333) / 10000;
+    let spv_share = first_net - contractor_share - subcontractor_share;
+
+    assert_eq!(usdc_token.0.balance(&contractor), contractor_share);
+    assert_eq!(usdc_token.0.balance(&subcontractor), subcontractor_share);
+    assert_eq!(
+        usdc_token.0.balance(&spv),
+        spv_share,
+        "The last payee receives the rounding remainder"
+    );
+    assert_eq!(usdc_token.0.balance(&receiver_address), 0);
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), second_amount);
+
+    // The receiver's part of a dispute resolution is split between the payees as well
+    escrow_approver.dispute_milestone(&1, &approver_address);
+    let mut distributions: Map<Address, i128> = Map::new(&env);
+    distributions.set(approver_address.clone(), 1_000_000);
+    distributions.set(receiver_address.clone(), 1_000_000);
+    escrow_approver.resolve_milestone_dispute(&1, &dispute_resolver_address, &distributions);
+
+    let fee_share = (1_000_000 * ((second_amount * 30) / 10000)) / second_amount;
+    let receiver_net = 1_000_000 - fee_share;
+    assert_eq!(
+        usdc_token.0.balance(&approver_address),
+        1_000_000 - fee_share
+    );
+    assert_eq!(usdc_token.0.balance(&receiver_address), 0);
+    assert_eq!(
+        usdc_token.0.balance(&contractor),
+        contractor_share + (receiver_net * 5_000) / 10000
+    );
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 0);
+}
+
+#[test]
+fn test_resolve_dispute_leaves_no_dust_and_sweep_dust() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+    let third_party = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let amount: i128 = 1_000_003;
+    let platform_fee: u32 = 125;
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_dispute_dust"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee,
+        milestones: vec![
+            &env,
+            Milestone {
+                description: String::from_str(&env, "First milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, ""),
+                amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+        ],
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+    let treasury = test_data.treasury;
+
+    escrow_approver.initialize_escrow(&escrow_properties);
+    usdc_token.1.mint(&escrow_approver.address, &amount);
+
+    // Sweeping is only possible once the escrow is finished
+    let result = escrow_approver.try_sweep_dust(&platform_address);
+    assert_error(result, FundsError::EscrowNotFinished);
+
+    escrow_approver.dispute_escrow(
+        &approver_address,
+        &String::from_str(&env, "Work not delivered"),
+    );
+
+    // Amounts chosen so that every proportional fee share has a remainder
+    let mut distributions: Map<Address, i128> = Map::new(&env);
+    distributions.set(approver_address.clone(), 333_333);
+    distributions.set(service_provider_address.clone(), 333_337);
+    distributions.set(third_party.clone(), 333_333);
+    escrow_approver.resolve_dispute(&dispute_resolver_address, &distributions);
+
+    let protocol_fee = (amount * 30) / 10000;
+    let platform_commission = (amount * platform_fee as i128) / 10000;
+    let total_fees = protocol_fee + platform_commission;
+
+    assert_eq!(usdc_token.0.balance(&treasury), protocol_fee);
+    assert_eq!(usdc_token.0.balance(&platform_address), platform_commission);
+    assert_eq!(
+        usdc_token.0.balance(&approver_address),
+        333_333 - (333_333 * total_fees) / amount
+    );
+    assert_eq!(
+        usdc_token.0.balance(&third_party),
+        333_333 - (333_333 * total_fees) / amount
+    );
+    assert_eq!(
+        usdc_token.0.balance(&approver_address)
+            + usdc_token.0.balance(&service_provider_address)
+            + usdc_token.0.balance(&third_party),
+        amount - total_fees,
+        "The largest recipient absorbs the rounding remainder of the fees"
+    );
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 0);
+
+    let result = escrow_approver.try_sweep_dust(&platform_address);
+    assert_error(result, FundsError::EscrowBalanceIsZero);
+
+    // Rounding dust, at most one unit per payee, can be swept by the platform
+    usdc_token.1.mint(&escrow_approver.address, &1);
+
+    let result = escrow_approver.try_sweep_dust(&approver_address);
+    assert_error(result, ContractError::OnlyPlatformAddressExecuteThisFunction);
+
+    escrow_approver.sweep_dust(&platform_address);
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 0);
+    assert_eq!(
+        usdc_token.0.balance(&platform_address),
+        platform_commission + 1
+    );
+
+    // Anything larger is not dust, so it is not the platform's to take
+    usdc_token.1.mint(&escrow_approver.address, &7);
+    let result = escrow_approver.try_sweep_dust(&platform_address);
+    assert_error(result, FundsError::BalanceExceedsDust);
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 7);
+}
+
+#[test]
+fn test_quotes_match_actual_payouts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+    let subcontractor = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let first_amount: i128 = 3_000_001;
+    let second_amount: i128 = 7_000_000;
+    let amount = first_amount + second_amount;
+    let platform_fee: u32 = 200;
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_quotes"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee,
+        milestones: vec![
+            &env,
+            Milestone {
+                description: String::from_str(&env, "First milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, ""),
+                amount: first_amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+            Milestone {
+                description: String::from_str(&env, "Second milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, ""),
+                amount: second_amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+        ],
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![
+            &env,
+            Payee {
+                address: service_provider_address.clone(),
+                share_bps: 7_000,
+            },
+            Payee {
+                address: subcontractor.clone(),
+                share_bps: 3_000,
+            },
+        ],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+    let treasury = test_data.treasury;
+
+    escrow_approver.initialize_escrow(&escrow_properties);
+    submit_milestones(&escrow_approver, &service_provider_address, &[0, 1]);
+    usdc_token.1.mint(&escrow_approver.address, &amount);
+
+    // The release quote covers every milestone that has not been paid out yet
+    let quote = escrow_approver.quote_release();
+    assert_eq!(quote.protocol_fee, (amount * 30) / 10000);
+    assert_eq!(quote.platform_fee, (amount * platform_fee as i128) / 10000);
+    assert_eq!(quote.payouts.len(), 2);
+    let mut quoted_total = quote.protocol_fee + quote.platform_fee;
+    for payout in quote.payouts.iter() {
+        quoted_total += payout.amount;
+    }
+    assert_eq!(quoted_total, amount);
+    assert_eq!(
+        usdc_token.0.balance(&escrow_approver.address),
+        amount,
+        "Quoting does not move funds"
+    );
+
+    escrow_approver.approve_milestone(&0, &approver_address);
+    escrow_approver.release_milestone_funds(&0, &release_signer_address);
+
+    let quote = escrow_approver.quote_release();
+    assert_eq!(quote.protocol_fee, (second_amount * 30) / 10000);
+
+    // The resolution quote is exactly what resolving the dispute pays
+    escrow_approver.dispute_escrow(
+        &approver_address,
+        &String::from_str(&env, "Work not delivered"),
+    );
+
+    let mut distributions: Map<Address, i128> = Map::new(&env);
+    distributions.set(approver_address.clone(), 2_333_333);
+    distributions.set(service_provider_address.clone(), 4_666_667);
+
+    let quote = escrow_approver.quote_resolution(&distributions);
+
+    let treasury_before = usdc_token.0.balance(&treasury);
+    let platform_before = usdc_token.0.balance(&platform_address);
+    let provider_before = usdc_token.0.balance(&service_provider_address);
+    let subcontractor_before = usdc_token.0.balance(&subcontractor);
+
+    escrow_approver.resolve_dispute(&dispute_resolver_address, &distributions);
+
+    assert_eq!(
+        usdc_token.0.balance(&treasury) - treasury_before,
+        quote.protocol_fee
+    );
+    assert_eq!(
+        usdc_token.0.balance(&platform_address) - platform_before,
+        quote.platform_fee
+    );
+    assert_eq!(quote.payouts.len(), 3);
+    for payout in quote.payouts.iter() {
+        if payout.recipient == approver_address {
+            assert_eq!(usdc_token.0.balance(&approver_address), payout.amount);
+        } else if payout.recipient == service_provider_address {
+            assert_eq!(
+                usdc_token.0.balance(&service_provider_address) - provider_before,
+                payout.amount
+            );
+        } else {
+            assert_eq!(payout.recipient, subcontractor);
+            assert_eq!(
+                usdc_token.0.balance(&subcontractor) - subcontractor_before,
+                payout.amount
+            );
+        }
+    }
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 0);
+
+    let mut invalid: Map<Address, i128> = Map::new(&env);
+    invalid.set(approver_address.clone(), 0);
+    let result = escrow_approver.try_quote_resolution(&invalid);
+    assert_error(result, ContractError::AmountsToBeTransferredShouldBePositive);
+}
+
+#[test]
+fn test_cancel_escrow_refunds_depositors_and_closes_it() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let approver_address = Address::generate(&env);
+    let investor_address = Address::generate(&env);
+    let service_provider_address = Address::generate(&env);
+    let platform_address = Address::generate(&env);
+    let release_signer_address = Address::generate(&env);
+    let dispute_resolver_address = Address::generate(&env);
+
+    let usdc_token = create_usdc_token(&env, &admin);
+
+    let amount: i128 = 10_000_000;
+    let approver_deposit: i128 = 6_000_000;
+    let investor_deposit: i128 = 4_000_000;
+
+    let escrow_properties: Escrow = Escrow {
+        engagement_id: String::from_str(&env, "test_cancel"),
+        title: String::from_str(&env, "Test Escrow"),
+        description: String::from_str(&env, "Test Escrow Description"),
+        roles: Roles {
+            approver: approver_address.clone(),
+            service_provider: service_provider_address.clone(),
+            platform_address: platform_address.clone(),
+            release_signer: release_signer_address.clone(),
+            dispute_resolver: dispute_resolver_address.clone(),
+            receiver: service_provider_address.clone(),
+        },
+        amount,
+        platform_fee: 0,
+        milestones: vec![
+            &env,
+            Milestone {
+                description: String::from_str(&env, "First milestone"),
+                status: MilestoneStatus::Pending,
+                evidence: String::from_str(&env, ""),
+                amount,
+                approved: false,
+                released: false,
+                rejection_reason: None,
+                rejection_count: 0,
+                disputed: false,
+                resolved: false,
+                depends_on: vec![&env],
+            },
+        ],
+        flags: Flags {
+            disputed: false,
+            released: false,
+            resolved: false,
+            cancelled: false,
+        },
+        trustline: Trustline {
+            address: usdc_token.0.address.clone(),
+        },
+        receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
+    };
+
+    // An escrow with an approved milestone can no longer be cancelled
+    let approved_escrow = create_escrow_contract(&env).client;
+    approved_escrow.initialize_escrow(&escrow_properties);
+    submit_milestones(&approved_escrow, &service_provider_address, &[0]);
+    approved_escrow.approve_milestone(&0, &approver_address);
+    let result = approved_escrow.try_cancel_escrow(&platform_address, &approver_address);
+    assert_error(result, FundsError::EscrowHasApprovedMilestones);
+
+    let test_data = create_escrow_contract(&env);
+    let escrow_approver = test_data.client;
+    escrow_approver.initialize_escrow(&escrow_properties);
+
+    usdc_token.1.mint(&approver_address, &approver_deposit);
+    usdc_token.1.mint(&investor_address, &investor_deposit);
+    escrow_approver.fund_escrow(&approver_address, &escrow_properties, &approver_deposit);
+    escrow_approver.fund_escrow(&investor_address, &escrow_properties, &investor_deposit);
+
+    // Both the platform and the approver have to consent
+    let result = escrow_approver.try_cancel_escrow(&platform_address, &investor_address);
+    assert_error(result, FundsError::UnauthorizedToCancelEscrow);
+    let result = escrow_approver.try_cancel_escrow(&investor_address, &approver_address);
+    assert_error(result, ContractError::OnlyPlatformAddressExecuteThisFunction);
+
+    escrow_approver.cancel_escrow(&platform_address, &approver_address);
+
+    assert_eq!(usdc_token.0.balance(&escrow_approver.address), 0);
+    assert_eq!(usdc_token.0.balance(&approver_address), approver_deposit);
+    assert_eq!(usdc_token.0.balance(&investor_address), investor_deposit);
+    assert!(escrow_approver.get_deposits().is_empty());
+    assert_eq!(escrow_approver.get_deposit(&investor_address), 0);
+
+    let cancelled_escrow = escrow_approver.get_escrow();
+    assert!(cancelled_escrow.flags.cancelled);
+
+    let result = escrow_approver.try_cancel_escrow(&platform_address, &approver_address);
+    assert_error(result, ContractError::EscrowCancelled);
+
+    usdc_token.1.mint(&investor_address, &investor_deposit);
+    let result =
+        escrow_approver.try_fund_escrow(&investor_address, &cancelled_escrow, &investor_deposit);
+    assert_error(result, ContractError::EscrowCancelled);
+
+    let result = escrow_approver.try_approve_milestone(&0, &approver_address);
+    assert_error(result, ContractError::EscrowCancelled);
+
+    let result = escrow_approver.try_change_milestone_status(
+        &0,
+        &MilestoneStatus::Submitted,
+        &None,
+        &service_provider_address,
+    );
+    assert_error(result, ContractError::EscrowCancelled);
+
+    let result = escrow_approver.try_dispute_escrow(
+        &approver_address,
+        &String::from_str(&env, "Work not delivered"),
+    );
+    assert_error(result, ContractError::EscrowCancelled);
+
+    let result = escrow_approver.try_update_escrow(&platform_address, &escrow_properties);
+    assert_error(result, ContractError::EscrowCancelled);
+
+    let result = escrow_approver.try_release_funds(&release_signer_address);
+    assert_error(result, ContractError::EscrowCancelled);
+}
+
+#[test]
+fn test_transfer_role_needs_only_the_current_holder() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize_and_fund();
+
+    let new_approver = Address::generate(&setup.env);
+    setup.client.transfer_role(&Role::Approver, &new_approver);
+
+    assert_eq!(last_signers(&setup.env), std::vec![setup.approver.clone()]);
+    assert_eq!(setup.client.get_escrow().roles.approver, new_approver);
+}
+
+#[test]
+fn test_previous_approver_cannot_approve_after_transfer() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize_and_fund();
+    setup.submit(0);
+
+    let new_approver = Address::generate(&setup.env);
+    setup.client.transfer_role(&Role::Approver, &new_approver);
+
+    let result = setup.client.try_approve_milestone(&0, &setup.approver);
+    assert_error(result, ContractError::OnlyApproverChangeMilstoneFlag);
+
+    setup.client.approve_milestone(&0, &new_approver);
+    assert!(setup.milestone(0).approved);
+}
+
+#[test]
+fn test_transferring_the_receiver_needs_the_platform() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize_and_fund();
+
+    let new_receiver = Address::generate(&setup.env);
+    setup.client.transfer_role(&Role::Receiver, &new_receiver);
+    assert_eq!(
+        last_signers(&setup.env),
+        std::vec![setup.service_provider.clone(), setup.platform.clone()]
+    );
+
+    setup.submit(0);
+    setup.client.approve_milestone(&0, &setup.approver);
+    setup
+        .client
+        .release_milestone_funds(&0, &setup.release_signer);
+
+    let protocol_fee = setup.escrow.amount * 30 / 10_000;
+    assert_eq!(
+        setup.token.balance(&new_receiver),
+        setup.escrow.amount - protocol_fee
+    );
+    assert_eq!(setup.token.balance(&setup.service_provider), 0);
+}
+
+#[test]
+fn test_transfer_role_to_its_current_holder() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+
+    let result = setup
+        .client
+        .try_transfer_role(&Role::Receiver, &setup.service_provider);
+    assert_error(result, WorkflowError::RoleAlreadyAssigned);
+}
+
+#[test]
+fn test_transfer_role_cannot_move_the_platform() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+
+    let result = setup
+        .client
+        .try_transfer_role(&Role::PlatformAddress, &Address::generate(&setup.env));
+    assert_error(result, ContractError::PlatformAddressCannotBeChanged);
+}
+
+#[test]
+fn test_transfer_role_keeps_the_appeal_resolver_independent() {
+    let (setup, appeal_resolver) = appealable_escrow_setup();
+    setup.initialize();
+
+    let result = setup
+        .client
+        .try_transfer_role(&Role::DisputeResolver, &appeal_resolver);
+    assert_error(result, DisputeError::AppealResolverCannotBeDisputeResolver);
+    assert_eq!(
+        setup.client.get_escrow().roles.dispute_resolver,
+        setup.dispute_resolver
+    );
+}
+
+/// An escrow whose milestones need two approvals out of the approver and two co-approvers.
+fn co_approved_escrow_setup<'a>() -> (EscrowSetup<'a>, Address, Address) {
+    let mut setup = EscrowSetup::new(&[10_000_000]);
+    let first_co_approver = Address::generate(&setup.env);
+    let second_co_approver = Address::generate(&setup.env);
+    setup.escrow.co_approvers = vec![
+        &setup.env,
+        first_co_approver.clone(),
+        second_co_approver.clone(),
+    ];
+    setup.escrow.approval_threshold = 2;
+    (setup, first_co_approver, second_co_approver)
+}
+
+#[test]
+fn test_approval_threshold_cannot_exceed_the_approvers() {
+    let (mut setup, _, _) = co_approved_escrow_setup();
+    setup.escrow.approval_threshold = 4;
+
+    let result = setup.client.try_initialize_escrow(&setup.escrow);
+    assert_error(result, WorkflowError::InvalidApprovalThreshold);
+}
+
+#[test]
+fn test_co_approvers_must_be_unique() {
+    let (mut setup, _, _) = co_approved_escrow_setup();
+    setup.escrow.co_approvers = vec![&setup.env, setup.approver.clone()];
+
+    let result = setup.client.try_initialize_escrow(&setup.escrow);
+    assert_error(result, WorkflowError::DuplicateApprover);
+}
+
+#[test]
+fn test_milestone_is_approved_once_the_threshold_is_reached() {
+    let (setup, first_co_approver, second_co_approver) = co_approved_escrow_setup();
+    setup.initialize();
+    setup.submit(0);
+
+    // A single approval does not reach the threshold
+    setup.client.approve_milestone(&0, &first_co_approver);
+    assert!(!setup.milestone(0).approved);
+    assert_eq!(
+        setup.client.get_milestone_approvals(&0),
+        vec![&setup.env, first_co_approver.clone()]
+    );
+
+    setup.client.approve_milestone(&0, &second_co_approver);
+    let milestone = setup.milestone(0);
+    assert!(milestone.approved);
+    assert_eq!(milestone.status, MilestoneStatus::Approved);
+}
+
+#[test]
+fn test_co_approver_cannot_approve_twice() {
+    let (setup, first_co_approver, _) = co_approved_escrow_setup();
+    setup.initialize();
+    setup.submit(0);
+
+    setup.client.approve_milestone(&0, &first_co_approver);
+    let result = setup.client.try_approve_milestone(&0, &first_co_approver);
+    assert_error(result, WorkflowError::MilestoneAlreadyApprovedBySigner);
+}
+
+#[test]
+fn test_outsider_cannot_approve_a_co_approved_milestone() {
+    let (setup, _, _) = co_approved_escrow_setup();
+    setup.initialize();
+    setup.submit(0);
+
+    let result = setup
+        .client
+        .try_approve_milestone(&0, &Address::generate(&setup.env));
+    assert_error(result, ContractError::OnlyApproverChangeMilstoneFlag);
+}
+
+#[test]
+fn test_withdraw_milestone_approval() {
+    let (setup, first_co_approver, _) = co_approved_escrow_setup();
+    setup.initialize();
+    setup.submit(0);
+
+    setup.client.approve_milestone(&0, &first_co_approver);
+    setup
+        .client
+        .withdraw_milestone_approval(&0, &first_co_approver);
+    assert!(setup.client.get_milestone_approvals(&0).is_empty());
+
+    let result = setup
+        .client
+        .try_withdraw_milestone_approval(&0, &first_co_approver);
+    assert_error(result, WorkflowError::MilestoneApprovalNotFound);
+}
+
+#[test]
+fn test_approval_cannot_be_withdrawn_once_the_threshold_is_reached() {
+    let (setup, first_co_approver, second_co_approver) = co_approved_escrow_setup();
+    setup.initialize();
+    setup.submit(0);
+
+    setup.client.approve_milestone(&0, &first_co_approver);
+    setup.client.approve_milestone(&0, &second_co_approver);
+
+    let result = setup
+        .client
+        .try_withdraw_milestone_approval(&0, &second_co_approver);
+    assert_error(result, ContractError::MilestoneHasAlreadyBeenApproved);
+}
+
+#[test]
+fn test_resubmitting_a_milestone_clears_its_approvals() {
+    let (setup, first_co_approver, second_co_approver) = co_approved_escrow_setup();
+    setup.initialize();
+    setup.submit(0);
+    setup.client.approve_milestone(&0, &first_co_approver);
+
+    // Approvals given to the earlier submission do not carry over
+    setup.submit(0);
+    assert!(setup.client.get_milestone_approvals(&0).is_empty());
+
+    setup.client.approve_milestone(&0, &second_co_approver);
+    assert_eq!(setup.milestone(0).status, MilestoneStatus::Submitted);
+    assert!(!setup.milestone(0).approved);
+}
+
+const PANEL_VOTING_PERIOD: u64 = 1_000;
+
+/// An escrow with two milestones whose disputes go to a panel of three resolvers with a
+/// quorum of two. The dispute resolver is the lead resolver.
+fn panel_escrow_setup<'a>() -> (EscrowSetup<'a>, [Address; 3]) {
+    let mut setup = EscrowSetup::new(&[5_000_000, 5_000_000]);
+    let resolvers = [
+        Address::generate(&setup.env),
+        Address::generate(&setup.env),
+        Address::generate(&setup.env),
+    ];
+    setup.escrow.arbitration = Arbitration::Panel(ArbitrationPanel {
+        resolvers: Vec::from_slice(&setup.env, &resolvers),
+        quorum: 2,
+        voting_period: PANEL_VOTING_PERIOD,
+    });
+    (setup, resolvers)
+}
+
+#[test]
+fn test_panel_quorum_cannot_exceed_the_resolvers() {
+    let (mut setup, resolvers) = panel_escrow_setup();
+    setup.escrow.arbitration = Arbitration::Panel(ArbitrationPanel {
+        resolvers: vec![&setup.env, resolvers[0].clone()],
+        quorum: 2,
+        voting_period: PANEL_VOTING_PERIOD,
+    });
+
+    let result = setup.client.try_initialize_escrow(&setup.escrow);
+    assert_error(result, DisputeError::InvalidDisputeQuorum);
+}
+
+#[test]
+fn test_milestone_dispute_opens_a_panel_vote() {
+    let (setup, _) = panel_escrow_setup();
+    setup.initialize_and_fund();
+    setup.submit(0);
+
+    setup.env.ledger().with_mut(|li| li.timestamp = 1_000);
+    setup.client.dispute_milestone(&0, &setup.approver);
+
+    let voting = setup
+        .client
+        .get_dispute_voting(&DisputeTarget::Milestone(0));
+    assert_eq!(voting.deadline, 1_000 + PANEL_VOTING_PERIOD);
+    assert!(voting.votes.is_empty());
+}
+
+#[test]
+fn test_lead_resolver_waits_for_the_panel_vote() {
+    let (setup, _) = panel_escrow_setup();
+    setup.initialize_and_fund();
+    setup.submit(0);
+    setup.client.dispute_milestone(&0, &setup.approver);
+
+    let distributions = create_distributions(&setup.env, &[(&setup.approver, 5_000_000)]);
+    let result = setup.client.try_resolve_milestone_dispute(
+        &0,
+        &setup.dispute_resolver,
+        &distributions,
+    );
+    assert_error(result, DisputeError::DisputeVotingOpen);
+}
+
+#[test]
+fn test_only_panel_resolvers_vote() {
+    let (setup, _) = panel_escrow_setup();
+    setup.initialize_and_fund();
+    setup.submit(0);
+    setup.client.dispute_milestone(&0, &setup.approver);
+
+    let distributions = create_distributions(&setup.env, &[(&setup.approver, 5_000_000)]);
+    let result = setup.client.try_vote_milestone_resolution(
+        &0,
+        &setup.dispute_resolver,
+        &distributions,
+    );
+    assert_error(result, DisputeError::OnlyArbitratorCanVote);
+}
+
+#[test]
+fn test_panel_resolves_once_a_quorum_agrees() {
+    let (setup, resolvers) = panel_escrow_setup();
+    setup.initialize_and_fund();
+    setup.submit(0);
+    setup.client.dispute_milestone(&0, &setup.approver);
+
+    let split_distributions = create_distributions(
+        &setup.env,
+        &[
+            (&setup.service_provider, 3_000_000),
+            (&setup.approver, 2_000_000),
+        ],
+    );
+    let refund_distributions =
+        create_distributions(&setup.env, &[(&setup.approver, 5_000_000)]);
+
+    setup
+        .client
+        .vote_milestone_resolution(&0, &resolvers[0], &split_distributions);
+    setup
+        .client
+        .vote_milestone_resolution(&0, &resolvers[1], &refund_distributions);
+    assert!(setup.milestone(0).disputed);
+    assert_eq!(
+        setup
+            .client
+            .get_dispute_voting(&DisputeTarget::Milestone(0))
+            .votes
+            .len(),
+        2
+    );
+
+    // A resolver can change its vote; two matching votes reach the quorum
+    setup
+        .client
+        .vote_milestone_resolution(&0, &resolvers[1], &split_distributions);
+    let milestone = setup.milestone(0);
+    assert!(milestone.resolved);
+    assert!(!milestone.disputed);
+    assert_eq!(setup.token.balance(&setup.client.address), 5_000_000);
+
+    let result = setup
+        .client
+        .try_get_dispute_voting(&DisputeTarget::Milestone(0));
+    assert_error(result, DisputeError::DisputeVotingNotFound);
+}
+
+#[test]
+fn test_lead_resolver_settles_without_a_quorum_after_the_voting_period() {
+    let (setup, resolvers) = panel_escrow_setup();
+    setup.initialize_and_fund();
+
+    setup.env.ledger().with_mut(|li| li.timestamp = 1_000);
+    setup.client.dispute_escrow(
+        &setup.service_provider,
+        &String::from_str(&setup.env, "Work not delivered"),
+    );
+
+    let refund_distributions =
+        create_distributions(&setup.env, &[(&setup.approver, setup.escrow.amount)]);
+    setup
+        .client
+        .vote_dispute_resolution(&resolvers[0], &refund_distributions);
+
+    setup
+        .env
+        .ledger()
+        .with_mut(|li| li.timestamp = 1_001 + PANEL_VOTING_PERIOD);
+    let result = setup
+        .client
+        .try_vote_dispute_resolution(&resolvers[1], &refund_distributions);
+    assert_error(result, DisputeError::DisputeVotingClosed);
+
+    setup
+        .client
+        .resolve_dispute(&setup.dispute_resolver, &refund_distributions);
+    let escrow = setup.client.get_escrow();
+    assert!(escrow.flags.resolved);
+    assert!(!escrow.flags.disputed);
+    assert_eq!(setup.token.balance(&setup.client.address), 0);
+
+    let result = setup.client.try_get_dispute_voting(&DisputeTarget::Escrow);
+    assert_error(result, DisputeError::DisputeVotingNotFound);
+}
+
+#[test]
+fn test_dispute_record_not_found_before_a_dispute() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize_and_fund();
+
+    let result = setup.client.try_get_dispute_record(&0);
+    assert_error(result, DisputeError::DisputeRecordNotFound);
+}
+
+#[test]
+fn test_dispute_requires_a_reason() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize_and_fund();
+
+    let result = setup
+        .client
+        .try_dispute_escrow(&setup.approver, &String::from_str(&setup.env, ""));
+    assert_error(result, DisputeError::EmptyDisputeReason);
+}
+
+#[test]
+fn test_dispute_statement_requires_an_open_dispute() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize_and_fund();
+
+    let statement = create_evidence(&setup.env, "ipfs://approver-statement", 1);
+    let result = setup.client.try_submit_dispute_statement(
+        &setup.approver,
+        &statement.uri,
+        &statement.hash,
+    );
+    assert_error(result, ContractError::EscrowNotInDispute);
+}
+
+#[test]
+fn test_only_escrow_roles_submit_dispute_statements() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize_and_fund();
+    setup.client.dispute_escrow(
+        &setup.approver,
+        &String::from_str(&setup.env, "Late delivery"),
+    );
+
+    let statement = create_evidence(&setup.env, "ipfs://outsider-statement", 1);
+    let result = setup.client.try_submit_dispute_statement(
+        &Address::generate(&setup.env),
+        &statement.uri,
+        &statement.hash,
+    );
+    assert_error(result, DisputeError::UnauthorizedToSubmitDisputeStatement);
+}
+
+#[test]
+fn test_dispute_record_keeps_the_reason_and_statements() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize_and_fund();
+
+    let approver_statement = create_evidence(&setup.env, "ipfs://approver-statement", 1);
+    let provider_statement = create_evidence(&setup.env, "ipfs://provider-statement", 2);
+
+    setup.env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let reason = String::from_str(&setup.env, "Deliverable does not match the specification");
+    setup.client.dispute_escrow(&setup.approver, &reason);
+
+    setup.env.ledger().with_mut(|li| li.timestamp = 2_000);
+    setup.client.submit_dispute_statement(
+        &setup.approver,
+        &approver_statement.uri,
+        &approver_statement.hash,
+    );
+    setup.client.submit_dispute_statement(
+        &setup.service_provider,
+        &provider_statement.uri,
+        &provider_statement.hash,
+    );
+
+    let distributions =
+        create_distributions(&setup.env, &[(&setup.approver, setup.escrow.amount)]);
+    setup
+        .client
+        .resolve_dispute(&setup.dispute_resolver, &distributions);
+
+    // The record outlives the dispute so the resolution can be audited
+    let record = setup.client.get_dispute_record(&0);
+    assert_eq!(record.opened_by, setup.approver);
+    assert_eq!(record.opened_at, 1_000);
+    assert_eq!(record.reason, reason);
+    assert_eq!(record.statements.len(), 2);
+
+    let statement = record.statements.get(1).unwrap();
+    assert_eq!(statement.submitter, setup.service_provider);
+    assert_eq!(statement.timestamp, 2_000);
+    assert_eq!(statement.uri, provider_statement.uri);
+    assert_eq!(statement.hash, provider_statement.hash);
+}
+
+#[test]
+fn test_resolved_escrow_cannot_be_disputed_again() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize_and_fund();
+    let reason = String::from_str(&setup.env, "Late delivery");
+    setup.client.dispute_escrow(&setup.approver, &reason);
+
+    let distributions =
+        create_distributions(&setup.env, &[(&setup.approver, setup.escrow.amount)]);
+    setup
+        .client
+        .resolve_dispute(&setup.dispute_resolver, &distributions);
+
+    let result = setup
+        .client
+        .try_dispute_escrow(&setup.service_provider, &reason);
+    assert_error(result, ContractError::EscrowAlreadyResolved);
+
+    // The record of the resolved dispute is left as it was
+    assert_eq!(setup.client.get_dispute_count(), 1);
+    assert_eq!(setup.client.get_dispute_record(&0).reason, reason);
+    let result = setup.client.try_get_dispute_record(&1);
+    assert_error(result, DisputeError::DisputeRecordNotFound);
+}
+
+#[test]
+fn test_released_escrow_cannot_be_disputed() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize_and_fund();
+    let reason = String::from_str(&setup.env, "Late delivery");
+    setup.submit(0);
+    setup.client.approve_milestone(&0, &setup.approver);
+    setup.client.release_funds(&setup.release_signer);
+
+    let result = setup.client.try_dispute_escrow(&setup.approver, &reason);
+    assert_error(result, ContractError::EscrowAlreadyReleased);
+    assert_eq!(setup.client.get_dispute_count(), 0);
+}
+
+/// An escrow whose dispute resolutions can be appealed for 100 ledgers. Returns the appeal
+/// resolver.
+fn appealable_escrow_setup<'a>() -> (EscrowSetup<'a>, Address) {
+    let mut setup = EscrowSetup::new(&[10_000_000]);
+    let appeal_resolver = Address::generate(&setup.env);
+    setup.escrow.appeal = Appeal::Window(AppealWindow {
+        ledgers: 100,
+        appeal_resolver: appeal_resolver.clone(),
+    });
+    (setup, appeal_resolver)
+}
+
+/// Funds the escrow, disputes it and has the dispute resolver award everything to the
+/// approver at ledger 10.
+fn post_escrow_resolution(setup: &EscrowSetup) -> Map<Address, i128> {
+    setup.initialize_and_fund();
+    setup.client.dispute_escrow(
+        &setup.approver,
+        &String::from_str(&setup.env, "Late delivery"),
+    );
+
+    setup.env.ledger().with_mut(|li| li.sequence_number = 10);
+    let distributions =
+        create_distributions(&setup.env, &[(&setup.approver, setup.escrow.amount)]);
+    setup
+        .client
+        .resolve_dispute(&setup.dispute_resolver, &distributions);
+    distributions
+}
+
+#[test]
+fn test_appeal_resolver_cannot_be_the_dispute_resolver() {
+    let (mut setup, _) = appealable_escrow_setup();
+    setup.escrow.appeal = Appeal::Window(AppealWindow {
+        ledgers: 100,
+        appeal_resolver: setup.dispute_resolver.clone(),
+    });
+
+    // The appeal must go to someone other than the resolver being appealed
+    let result = setup.client.try_initialize_escrow(&setup.escrow);
+    assert_error(result, DisputeError::AppealResolverCannotBeDisputeResolver);
+}
+
+#[test]
+fn test_resolution_is_posted_during_the_appeal_window() {
+    let (setup, _) = appealable_escrow_setup();
+    let distributions = post_escrow_resolution(&setup);
+
+    // The decision is posted, but nothing is paid out during the window
+    assert_eq!(
+        setup.token.balance(&setup.client.address),
+        setup.escrow.amount
+    );
+    let pending = setup
+        .client
+        .get_pending_resolution(&DisputeTarget::Escrow);
+    assert_eq!(pending.decided_by, setup.dispute_resolver);
+    assert_eq!(pending.distributions, distributions);
+    assert_eq!(pending.total, setup.escrow.amount);
+    assert_eq!(pending.appealable_until, 110);
+    assert!(!pending.appealed);
+
+    let result = setup
+        .client
+        .try_resolve_dispute(&setup.dispute_resolver, &distributions);
+    assert_error(result, DisputeError::ResolutionPending);
+
+    let result = setup.client.try_finalize_resolution(&DisputeTarget::Escrow);
+    assert_error(result, DisputeError::AppealWindowOpen);
+}
+
+#[test]
+fn test_only_counterparties_appeal_resolutions() {
+    let (setup, _) = appealable_escrow_setup();
+    post_escrow_resolution(&setup);
+
+    let result = setup
+        .client
+        .try_appeal_resolution(&Address::generate(&setup.env), &DisputeTarget::Escrow);
+    assert_error(result, DisputeError::UnauthorizedToAppeal);
+}
+
+#[test]
+fn test_resolve_appeal_requires_an_appeal() {
+    let (setup, appeal_resolver) = appealable_escrow_setup();
+    post_escrow_resolution(&setup);
+
+    let distributions =
+        create_distributions(&setup.env, &[(&setup.service_provider, setup.escrow.amount)]);
+    let result =
+        setup
+            .client
+            .try_resolve_appeal(&appeal_resolver, &DisputeTarget::Escrow, &distributions);
+    assert_error(result, DisputeError::ResolutionNotAppealed);
+}
+
+#[test]
+fn test_resolution_can_only_be_appealed_once() {
+    let (setup, _) = appealable_escrow_setup();
+    post_escrow_resolution(&setup);
+
+    setup
+        .client
+        .appeal_resolution(&setup.service_provider, &DisputeTarget::Escrow);
+    let result = setup
+        .client
+        .try_appeal_resolution(&setup.approver, &DisputeTarget::Escrow);
+    assert_error(result, DisputeError::ResolutionAlreadyAppealed);
+}
+
+#[test]
+fn test_appealed_resolution_cannot_be_finalized() {
+    let (setup, _) = appealable_escrow_setup();
+    post_escrow_resolution(&setup);
+    setup
+        .client
+        .appeal_resolution(&setup.service_provider, &DisputeTarget::Escrow);
+
+    setup.env.ledger().with_mut(|li| li.sequence_number = 200);
+    let result = setup.client.try_finalize_resolution(&DisputeTarget::Escrow);
+    assert_error(result, DisputeError::ResolutionAlreadyAppealed);
+}
+
+#[test]
+fn test_only_the_appeal_resolver_settles_appeals() {
+    let (setup, _) = appealable_escrow_setup();
+    post_escrow_resolution(&setup);
+    setup
+        .client
+        .appeal_resolution(&setup.service_provider, &DisputeTarget::Escrow);
+
+    let distributions =
+        create_distributions(&setup.env, &[(&setup.service_provider, setup.escrow.amount)]);
+    let result = setup.client.try_resolve_appeal(
+        &setup.dispute_resolver,
+        &DisputeTarget::Escrow,
+        &distributions,
+    );
+    assert_error(result, DisputeError::OnlyAppealResolverCanExecuteThisFunction);
+}
+
+#[test]
+fn test_appeal_resolver_decision_is_paid_out() {
+    let (setup, appeal_resolver) = appealable_escrow_setup();
+    post_escrow_resolution(&setup);
+    setup
+        .client
+        .appeal_resolution(&setup.service_provider, &DisputeTarget::Escrow);
+
+    let distributions =
+        create_distributions(&setup.env, &[(&setup.service_provider, setup.escrow.amount)]);
+    setup
+        .client
+        .resolve_appeal(&appeal_resolver, &DisputeTarget::Escrow, &distributions);
+
+    let protocol_fee = setup.escrow.amount * 30 / 10_000;
+    assert_eq!(setup.token.balance(&setup.client.address), 0);
+    assert_eq!(setup.token.balance(&setup.approver), 0);
+    assert_eq!(
+        setup.token.balance(&setup.service_provider),
+        setup.escrow.amount - protocol_fee
+    );
+
+    let escrow = setup.client.get_escrow();
+    assert!(escrow.flags.resolved);
+    assert!(!escrow.flags.disputed);
+
+    let result = setup
+        .client
+        .try_get_pending_resolution(&DisputeTarget::Escrow);
+    assert_error(result, DisputeError::PendingResolutionNotFound);
+}
+
+#[test]
+fn test_unappealed_milestone_resolution_is_finalized_after_the_window() {
+    let (setup, _) = appealable_escrow_setup();
+    setup.initialize_and_fund();
+    setup.client.dispute_milestone(&0, &setup.approver);
+
+    setup.env.ledger().with_mut(|li| li.sequence_number = 10);
+    let distributions =
+        create_distributions(&setup.env, &[(&setup.approver, setup.escrow.amount)]);
+    setup
+        .client
+        .resolve_milestone_dispute(&0, &setup.dispute_resolver, &distributions);
+
+    let target = DisputeTarget::Milestone(0);
+    assert_eq!(
+        setup.client.get_pending_resolution(&target).appealable_until,
+        110
+    );
+
+    setup.env.ledger().with_mut(|li| li.sequence_number = 111);
+    let result = setup
+        .client
+        .try_appeal_resolution(&setup.service_provider, &target);
+    assert_error(result, DisputeError::AppealWindowClosed);
+
+    // Anyone may finalize it once the window has closed
+    setup.client.finalize_resolution(&target);
+
+    let protocol_fee = setup.escrow.amount * 30 / 10_000;
+    assert_eq!(setup.token.balance(&setup.client.address), 0);
+    assert_eq!(
+        setup.token.balance(&setup.approver),
+        setup.escrow.amount - protocol_fee
+    );
+
+    let escrow = setup.client.get_escrow();
+    let milestone = escrow.milestones.get(0).unwrap();
+    assert!(milestone.resolved);
+    assert!(!milestone.disputed);
+    assert!(escrow.flags.released);
+}
+
+const REVIEW_PERIOD: u64 = 3 * 24 * 60 * 60;
+
+/// An escrow with two milestones that anyone may approve once their review period has
+/// passed since they were submitted.
+fn auto_approved_escrow_setup<'a>() -> EscrowSetup<'a> {
+    let mut setup = EscrowSetup::new(&[5_000_000, 5_000_000]);
+    setup.escrow.review_period = Some(REVIEW_PERIOD);
+    setup
+}
+
+#[test]
+fn test_review_period_must_be_positive() {
+    let mut setup = auto_approved_escrow_setup();
+    setup.escrow.review_period = Some(0);
+
+    let result = setup.client.try_initialize_escrow(&setup.escrow);
+    assert_error(result, WorkflowError::InvalidReviewPeriod);
+}
+
+#[test]
+fn test_auto_approve_requires_a_submission() {
+    let setup = auto_approved_escrow_setup();
+    setup.initialize();
+
+    let result = setup.client.try_auto_approve(&0);
+    assert_error(result, WorkflowError::MilestoneNotSubmitted);
+}
+
+#[test]
+fn test_auto_approve_after_the_review_period() {
+    let setup = auto_approved_escrow_setup();
+    setup.initialize();
+
+    setup.env.ledger().with_mut(|li| li.timestamp = 1_000);
+    setup.submit(0);
+
+    setup
+        .env
+        .ledger()
+        .with_mut(|li| li.timestamp = 1_000 + REVIEW_PERIOD - 1);
+    let result = setup.client.try_auto_approve(&0);
+    assert_error(result, WorkflowError::ReviewPeriodNotElapsed);
+
+    // Anyone may push the milestone through once the approver has stayed silent
+    setup
+        .env
+        .ledger()
+        .with_mut(|li| li.timestamp = 1_000 + REVIEW_PERIOD);
+    setup.client.auto_approve(&0);
+
+    let milestone = setup.milestone(0);
+    assert!(milestone.approved);
+    assert_eq!(milestone.status, MilestoneStatus::Approved);
+
+    let result = setup.client.try_auto_approve(&0);
+    assert_error(result, ContractError::MilestoneHasAlreadyBeenApproved);
+}
+
+#[test]
+fn test_rejection_stops_the_review_clock() {
+    let setup = auto_approved_escrow_setup();
+    setup.initialize();
+
+    setup.submit(1);
+    setup.client.reject_milestone(
+        &1,
+        &setup.approver,
+        &String::from_str(&setup.env, "Missing documentation"),
+    );
+
+    setup
+        .env
+        .ledger()
+        .with_mut(|li| li.timestamp += REVIEW_PERIOD);
+    let result = setup.client.try_auto_approve(&1);
+    assert_error(result, WorkflowError::MilestoneNotSubmitted);
+}
+
+#[test]
+fn test_milestone_dispute_stops_the_review_clock() {
+    let setup = auto_approved_escrow_setup();
+    setup.initialize();
+
+    setup.submit(1);
+    setup.client.dispute_milestone(&1, &setup.approver);
+
+    setup
+        .env
+        .ledger()
+        .with_mut(|li| li.timestamp += REVIEW_PERIOD);
+    let result = setup.client.try_auto_approve(&1);
+    assert_error(result, DisputeError::MilestoneOpenedForDisputeResolution);
+}
+
+#[test]
+fn test_auto_approve_requires_a_review_period() {
+    let setup = EscrowSetup::new(&[5_000_000, 5_000_000]);
+    setup.initialize();
+    setup.submit(0);
+
+    // Escrows without a review period are only approved by their approvers
+    setup
+        .env
+        .ledger()
+        .with_mut(|li| li.timestamp += REVIEW_PERIOD);
+    let result = setup.client.try_auto_approve(&0);
+    assert_error(result, WorkflowError::AutoApprovalNotEnabled);
+}
+
+#[test]
+fn test_milestone_dependencies_cannot_form_a_cycle() {
+    let mut setup = EscrowSetup::new(&[1_000_000, 1_000_000, 1_000_000]);
+    setup.set_depends_on(0, &[1]);
+    setup.set_depends_on(1, &[2]);
+    setup.set_depends_on(2, &[0]);
+
+    let result = setup.client.try_initialize_escrow(&setup.escrow);
+    assert_error(result, WorkflowError::CyclicMilestoneDependencies);
+}
+
+#[test]
+fn test_milestone_cannot_depend_on_itself() {
+    let mut setup = EscrowSetup::new(&[1_000_000, 1_000_000, 1_000_000]);
+    setup.set_depends_on(0, &[0]);
+
+    let result = setup.client.try_initialize_escrow(&setup.escrow);
+    assert_error(result, WorkflowError::InvalidMilestoneDependency);
+}
+
+#[test]
+fn test_milestone_dependencies_must_exist() {
+    let mut setup = EscrowSetup::new(&[1_000_000, 1_000_000, 1_000_000]);
+    setup.set_depends_on(2, &[3]);
+
+    let result = setup.client.try_initialize_escrow(&setup.escrow);
+    assert_error(result, WorkflowError::InvalidMilestoneDependency);
+}
+
+#[test]
+fn test_submitting_requires_approved_dependencies() {
+    let mut setup = EscrowSetup::new(&[1_000_000, 1_000_000, 1_000_000]);
+    setup.set_depends_on(1, &[0]);
+    setup.initialize();
+
+    let result = setup.client.try_change_milestone_status(
+        &1,
+        &MilestoneStatus::Submitted,
+        &None,
+        &setup.service_provider,
+    );
+    assert_error(result, WorkflowError::MilestoneDependenciesNotApproved);
+
+    // Work on a later stage can start, it just cannot be handed in yet
+    setup.client.change_milestone_status(
+        &1,
+        &MilestoneStatus::InProgress,
+        &None,
+        &setup.service_provider,
+    );
+    assert_eq!(setup.milestone(1).status, MilestoneStatus::InProgress);
+}
+
+#[test]
+fn test_approving_requires_approved_dependencies() {
+    let mut setup = EscrowSetup::new(&[1_000_000, 1_000_000, 1_000_000]);
+    setup.set_depends_on(1, &[0]);
+    setup.set_depends_on(2, &[0, 1]);
+    setup.initialize();
+    setup.set_status(2, MilestoneStatus::Submitted);
+
+    let result = setup.client.try_approve_milestone(&2, &setup.approver);
+    assert_error(result, WorkflowError::MilestoneDependenciesNotApproved);
+
+    setup.submit(0);
+    setup.client.approve_milestone(&0, &setup.approver);
+
+    // Milestone 2 still waits for milestone 1
+    let result = setup.client.try_approve_milestone(&2, &setup.approver);
+    assert_error(result, WorkflowError::MilestoneDependenciesNotApproved);
+
+    setup.submit(1);
+    setup.client.approve_milestone(&1, &setup.approver);
+    setup.client.approve_milestone(&2, &setup.approver);
+
+    let escrow = setup.client.get_escrow();
+    assert!(escrow.milestones.iter().all(|milestone| milestone.approved));
+}
+
+#[test]
+fn test_update_rejects_cyclic_milestone_dependencies() {
+    let mut setup = EscrowSetup::new(&[1_000_000, 1_000_000, 1_000_000]);
+    setup.set_depends_on(1, &[0]);
+    setup.initialize();
+
+    let mut updated_properties = setup.escrow.clone();
+    let mut first_milestone = updated_properties.milestones.get(0).unwrap();
+    first_milestone.depends_on = vec![&setup.env, 1];
+    updated_properties.milestones.set(0, first_milestone);
+
+    let result = setup
+        .client
+        .try_update_escrow(&setup.platform, &updated_properties);
+    assert_error(result, WorkflowError::CyclicMilestoneDependencies);
+}
+
+/// An escrow with three milestones. The last one depends on the second and has already been
+/// submitted.
+fn batch_escrow_setup<'a>() -> EscrowSetup<'a> {
+    let mut setup = EscrowSetup::new(&[1_000_000, 1_000_000, 1_000_000]);
+    setup.set_depends_on(2, &[1]);
+    setup.initialize();
+    setup.set_status(2, MilestoneStatus::Submitted);
+    setup
+}
+
+#[test]
+fn test_batch_status_change_rejects_an_empty_batch() {
+    let setup = batch_escrow_setup();
+
+    let result = setup
+        .client
+        .try_change_milestone_statuses(&vec![&setup.env], &setup.service_provider);
+    assert_error(result, WorkflowError::EmptyMilestoneBatch);
+}
+
+#[test]
+fn test_batch_status_change_rejects_duplicate_indexes() {
+    let setup = batch_escrow_setup();
+
+    let result = setup.client.try_change_milestone_statuses(
+        &vec![
+            &setup.env,
+            (0, MilestoneStatus::InProgress, None),
+            (0, MilestoneStatus::Submitted, None),
+        ],
+        &setup.service_provider,
+    );
+    assert_error(result, WorkflowError::DuplicateMilestoneIndex);
+}
+
+#[test]
+fn test_batch_status_change_is_all_or_nothing() {
+    let setup = batch_escrow_setup();
+    let evidence = create_evidence(&setup.env, "ipfs://q1-report", 1);
+
+    // One bad entry fails the whole batch
+    let result = setup.client.try_change_milestone_statuses(
+        &vec![
+            &setup.env,
+            (0, MilestoneStatus::Submitted, Some(evidence)),
+            (5, MilestoneStatus::Submitted, None),
+        ],
+        &setup.service_provider,
+    );
+    assert_error(result, ContractError::InvalidMileStoneIndex);
+    assert_eq!(setup.milestone(0).status, MilestoneStatus::Pending);
+    assert_eq!(setup.client.get_milestone_evidence(&0, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_batch_status_change_applies_every_entry() {
+    let setup = batch_escrow_setup();
+    let evidence = create_evidence(&setup.env, "ipfs://q1-report", 1);
+
+    setup.client.change_milestone_statuses(
+        &vec![
+            &setup.env,
+            (0, MilestoneStatus::Submitted, Some(evidence.clone())),
+            (1, MilestoneStatus::Submitted, None),
+        ],
+        &setup.service_provider,
+    );
+
+    let first_milestone = setup.milestone(0);
+    assert_eq!(first_milestone.status, MilestoneStatus::Submitted);
+    assert_eq!(first_milestone.evidence, evidence.uri);
+    assert_eq!(setup.milestone(1).status, MilestoneStatus::Submitted);
+    assert_eq!(setup.client.get_milestone_evidence(&0, &0, &10).len(), 1);
+}
+
+#[test]
+fn test_batch_approval_follows_the_dependency_order() {
+    let setup = batch_escrow_setup();
+    setup.submit(0);
+    setup.submit(1);
+
+    // Milestone 2 depends on milestone 1, so it has to come after it in the batch
+    let result = setup
+        .client
+        .try_approve_milestones(&vec![&setup.env, 2, 1], &setup.approver);
+    assert_error(result, WorkflowError::MilestoneDependenciesNotApproved);
+    assert!(!setup.milestone(1).approved);
+
+    setup
+        .client
+        .approve_milestones(&vec![&setup.env, 0, 1, 2], &setup.approver);
+
+    let escrow = setup.client.get_escrow();
+    assert!(escrow.milestones.iter().all(|milestone| milestone.approved));
+    assert!(escrow
+        .milestones
+        .iter()
+        .all(|milestone| milestone.status == MilestoneStatus::Approved));
+}
+
+/// Topics after the event name and data of the events `contract` published with `name`
+/// during the last invocation. The data holds the event fields sorted by name.
+fn contract_events(
+    env: &Env,
+    contract: &Address,
+    name: &str,
+) -> std::vec::Vec<(Vec<Val>, Vec<Val>)> {
+    let name = Symbol::new(env, name);
+    env.events()
+        .all()
+        .iter()
+        .filter(|(address, topics, _)| {
+            address == contract
+                && Symbol::try_from_val(env, &topics.get(0).unwrap()).ok() == Some(name.clone())
+        })
+        .map(|(_, topics, data)| {
+            (
+                topics.slice(1..),
+                Vec::<Val>::try_from_val(env, &data).unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_initialize_event_is_compact() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+
+    let events = contract_events(&setup.env, &setup.client.address, "tw_init");
+    assert_eq!(events.len(), 1);
+    let (topics, data) = &events[0];
+    assert_eq!(
+        Symbol::try_from_val(&setup.env, &topics.get(0).unwrap()).unwrap(),
+        Symbol::new(&setup.env, "v1")
+    );
+    assert_eq!(
+        String::try_from_val(&setup.env, &topics.get(1).unwrap()).unwrap(),
+        setup.escrow.engagement_id
+    );
+    assert_eq!(
+        i128::try_from_val(&setup.env, &data.get(0).unwrap()).unwrap(),
+        setup.escrow.amount
+    );
+    assert_eq!(u32::try_from_val(&setup.env, &data.get(1).unwrap()).unwrap(), 1);
+}
+
+#[test]
+fn test_milestone_status_change_event_is_compact() {
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize();
+    setup.submit(0);
+
+    let events = contract_events(&setup.env, &setup.client.address, "tw_ms_change");
+    assert_eq!(events.len(), 1);
+    let (topics, data) = &events[0];
+    assert_eq!(
+        String::try_from_val(&setup.env, &topics.get(1).unwrap()).unwrap(),
+        setup.escrow.engagement_id
+    );
+    assert_eq!(i128::try_from_val(&setup.env, &data.get(0).unwrap()).unwrap(), 0);
+    assert_eq!(
+        MilestoneStatus::try_from_val(&setup.env, &data.get(1).unwrap()).unwrap(),
+        MilestoneStatus::Submitted
+    );
+    assert_eq!(
+        MilestoneStatus::try_from_val(&setup.env, &data.get(2).unwrap()).unwrap(),
+        MilestoneStatus::Pending
+    );
+}
+
+/// Asserts that the events `contract_events` returned for `name` carry the `v1` layout
+/// version and the engagement id as topics.
+fn assert_versioned_events(setup: &EscrowSetup, name: &str, count: usize) {
+    let events = contract_events(&setup.env, &setup.client.address, name);
+    assert_eq!(events.len(), count, "{name}");
+    for (topics, _) in events.iter() {
+        assert_eq!(
+            Symbol::try_from_val(&setup.env, &topics.get(0).unwrap()).unwrap(),
+            Symbol::new(&setup.env, "v1")
+        );
+        assert_eq!(
+            String::try_from_val(&setup.env, &topics.get(1).unwrap()).unwrap(),
+            setup.escrow.engagement_id
+        );
+    }
+}
+
+#[test]
+fn test_change_events_carry_the_version_and_engagement_id() {
+    let setup = EscrowSetup::new(&[4_000_000, 6_000_000]);
+    setup.initialize_and_fund();
+    assert_versioned_events(&setup, "tw_fund", 1);
+
+    setup.client.change_milestone_status(
+        &0,
+        &MilestoneStatus::Submitted,
+        &Some(create_evidence(&setup.env, "ipfs://design", 1)),
+        &setup.service_provider,
+    );
+    assert_versioned_events(&setup, "tw_ms_evidence", 1);
+
+    setup.client.reject_milestone(
+        &0,
+        &setup.approver,
+        &String::from_str(&setup.env, "Incomplete"),
+    );
+    assert_versioned_events(&setup, "tw_ms_reject", 1);
+
+    setup
+        .client
+        .transfer_role(&Role::ReleaseSigner, &Address::generate(&setup.env));
+    assert_versioned_events(&setup, "tw_role", 1);
+
+    setup.client.dispute_milestone(&1, &setup.approver);
+    assert_versioned_events(&setup, "tw_ms_dispute", 1);
+
+    // A cancellation refunds the depositors
+    let setup = EscrowSetup::new(&[10_000_000]);
+    setup.initialize_and_fund();
+    setup.client.cancel_escrow(&setup.platform, &setup.approver);
+    assert_versioned_events(&setup, "tw_refund", 1);
+    assert_versioned_events(&setup, "tw_cancel", 1);
+}
+
+#[test]
+fn test_update_events_carry_only_the_changes() {
+    let setup = EscrowSetup::new(&[4_000_000, 6_000_000]);
+    setup.initialize();
+
+    let mut updated_properties = setup.escrow.clone();
+    updated_properties.title = String::from_str(&setup.env, "Renamed Escrow");
+    updated_properties.review_period = Some(3_600);
+    let mut milestone = updated_properties.milestones.get(1).unwrap();
+    milestone.description = String::from_str(&setup.env, "Launch");
+    updated_properties.milestones.set(1, milestone.clone());
+    setup
+        .client
+        .update_escrow(&setup.platform, &updated_properties);
+
+    let env = &setup.env;
+    assert_versioned_events(&setup, "tw_update", 1);
+    let (_, data) = &contract_events(env, &setup.client.address, "tw_update")[0];
+    let changes = EscrowChanges::try_from_val(env, &data.get(0).unwrap()).unwrap();
+    assert!(
+        changes.properties
+            == vec![
+                env,
+                EscrowPropertyChange::Title(updated_properties.title.clone()),
+                EscrowPropertyChange::ReviewPeriod(Some(3_600)),
+            ]
+    );
+    assert_eq!(changes.milestones.len(), 1);
+    assert!(changes.milestones.get(1).unwrap() == milestone);
+    assert_eq!(changes.milestone_count, 2);
+
+    // A proposal publishes what it would change, dropped milestones included
+    let mut proposed_properties = updated_properties.clone();
+    proposed_properties.roles.service_provider = Address::generate(env);
+    proposed_properties.milestones.pop_back();
+    proposed_properties.amount = 4_000_000;
+    proposed_properties.review_period = None;
+    setup
+        .client
+        .propose_escrow_update(&setup.platform, &proposed_properties);
+
+    assert_versioned_events(&setup, "tw_update_prop", 1);
+    let (_, data) = &contract_events(env, &setup.client.address, "tw_update_prop")[0];
+    let changes = EscrowChanges::try_from_val(env, &data.get(0).unwrap()).unwrap();
+    assert!(
+        changes.properties
+            == vec![
+                env,
+                EscrowPropertyChange::Roles(proposed_properties.roles.clone()),
+                EscrowPropertyChange::Amount(4_000_000),
+                EscrowPropertyChange::ReviewPeriod(None),
+            ]
+    );
+    assert!(changes.milestones.is_empty());
+    assert_eq!(changes.milestone_count, 1);
+}
+
+#[test]
+fn test_release_publishes_one_event_per_transfer() {
+    let mut setup = EscrowSetup::new(&[10_000_000]);
+    setup.escrow.platform_fee = 5 * 100;
+    setup.initialize_and_fund();
+    setup.submit(0);
+    setup.client.approve_milestone(&0, &setup.approver);
+    setup
+        .client
+        .release_milestone_funds(&0, &setup.release_signer);
+
+    // One event per transfer, adding up to what left the escrow
+    let amount = setup.escrow.amount;
+    let protocol_fee = amount * 30 / 10_000;
+    let platform_cut = amount * setup.escrow.platform_fee as i128 / 10_000;
+    let expected = [
+        (TransferKind::ProtocolFee, setup.treasury.clone(), protocol_fee),
+        (TransferKind::PlatformFee, setup.platform.clone(), platform_cut),
+        (
+            TransferKind::Payout,
+            setup.service_provider.clone(),
+            amount - protocol_fee - platform_cut,
+        ),
+    ];
+
+    let env = &setup.env;
+    let events = contract_events(env, &setup.client.address, "tw_transfer");
+    assert_eq!(events.len(), expected.len());
+    for ((topics, data), (kind, recipient, transferred)) in events.iter().zip(expected.iter()) {
+        assert_eq!(
+            String::try_from_val(env, &topics.get(1).unwrap()).unwrap(),
+            setup.escrow.engagement_id
+        );
+        assert_eq!(i128::try_from_val(env, &data.get(0).unwrap()).unwrap(), *transferred);
+        assert_eq!(TransferKind::try_from_val(env, &data.get(1).unwrap()).unwrap(), *kind);
+        assert_eq!(Address::try_from_val(env, &data.get(2).unwrap()).unwrap(), *recipient);
+        assert_eq!(setup.token.balance(recipient), *transferred);
+    }
+    assert_eq!(setup.token.balance(&setup.client.address), 0);
+}
+
+/// An escrow with a 5% platform fee whose receiver side is split between two payees.
+/// Returns the payees.
+fn split_escrow_setup<'a>() -> (EscrowSetup<'a>, Address, Address) {
+    let mut setup = EscrowSetup::new(&[6_000_001, 3_999_999]);
+    let contractor = Address::generate(&setup.env);
+    let subcontractor = Address::generate(&setup.env);
+    setup.escrow.platform_fee = 5 * 100;
+    setup.escrow.payees = vec![
+        &setup.env,
+        Payee {
+            address: contractor.clone(),
+            share_bps: 6_667,
+        },
+        Payee {
+            address: subcontractor.clone(),
+            share_bps: 3_333,
+        },
+    ];
+    (setup, contractor, subcontractor)
+}
+
+/// The payout breakdown the last invocation published.
+fn payout_breakdown(setup: &EscrowSetup) -> (Vec<Payout>, i128, i128) {
+    let events = contract_events(&setup.env, &setup.client.address, "tw_payout");
+    assert_eq!(events.len(), 1);
+    let (topics, data) = &events[0];
+    assert_eq!(
+        String::try_from_val(&setup.env, &topics.get(1).unwrap()).unwrap(),
+        setup.escrow.engagement_id
+    );
+    (
+        Vec::<Payout>::try_from_val(&setup.env, &data.get(0).unwrap()).unwrap(),
+        i128::try_from_val(&setup.env, &data.get(1).unwrap()).unwrap(),
+        i128::try_from_val(&setup.env, &data.get(2).unwrap()).unwrap(),
+    )
+}
+
+#[test]
+fn test_release_publishes_the_payout_breakdown() {
+    let (setup, contractor, subcontractor) = split_escrow_setup();
+    setup.initialize_and_fund();
+    setup.submit(0);
+    setup.client.approve_milestone(&0, &setup.approver);
+    setup
+        .client
+        .release_milestone_funds(&0, &setup.release_signer);
+
+    // Every payee's gross, fee share and net amount
+    let released: i128 = 6_000_001;
+    let protocol_fee = released * 30 / 10_000;
+    let platform_cut = released * setup.escrow.platform_fee as i128 / 10_000;
+
+    let (payouts, platform_fee, protocol_fee_paid) = payout_breakdown(&setup);
+    assert_eq!(platform_fee, platform_cut);
+    assert_eq!(protocol_fee_paid, protocol_fee);
+
+    assert_eq!(payouts.len(), 2);
+    assert_eq!(payouts.get(0).unwrap().recipient, contractor);
+    assert_eq!(payouts.get(0).unwrap().gross, released * 6_667 / 10_000);
+    assert_eq!(payouts.get(1).unwrap().recipient, subcontractor);
+
+    let mut gross_total: i128 = 0;
+    let mut fee_total: i128 = 0;
+    for payout in payouts.iter() {
+        assert_eq!(payout.gross - payout.fee_share, payout.amount);
+        assert_eq!(setup.token.balance(&payout.recipient), payout.amount);
+        gross_total += payout.gross;
+        fee_total += payout.fee_share;
+    }
+    assert_eq!(gross_total, released);
+    assert_eq!(fee_total, protocol_fee + platform_cut);
+}
+
+#[test]
+fn test_resolution_payout_breakdown_matches_its_quote() {
+    let (setup, contractor, _) = split_escrow_setup();
+    setup.initialize_and_fund();
+    setup.client.dispute_milestone(&1, &setup.approver);
+
+    let distributions = create_distributions(
+        &setup.env,
+        &[
+            (&setup.approver, 1_000_000),
+            (&setup.service_provider, 2_999_999),
+        ],
+    );
+    let quote = setup.client.quote_resolution(&distributions);
+    setup
+        .client
+        .resolve_milestone_dispute(&1, &setup.dispute_resolver, &distributions);
+
+    let (payouts, platform_fee, protocol_fee) = payout_breakdown(&setup);
+    assert_eq!(payouts, quote.payouts);
+    assert_eq!(platform_fee, quote.platform_fee);
+    assert_eq!(protocol_fee, quote.protocol_fee);
+
+    assert_eq!(payouts.len(), 3);
+    let mut gross_total: i128 = 0;
+    let mut fee_total: i128 = 0;
+    for payout in payouts.iter() {
+        assert_eq!(payout.gross - payout.fee_share, payout.amount);
+        assert_eq!(setup.token.balance(&payout.recipient), payout.amount);
+        gross_total += payout.gross;
+        fee_total += payout.fee_share;
+    }
+    assert_eq!(gross_total, 3_999_999);
+    assert_eq!(fee_total, quote.protocol_fee + quote.platform_fee);
+
+    let approver_payout = payouts
+        .iter()
+        .find(|payout| payout.recipient == setup.approver)
+        .unwrap();
+    assert_eq!(approver_payout.gross, 1_000_000);
+    assert!(payouts.iter().any(|payout| payout.recipient == contractor));
+    assert_eq!(
+        setup.token.balance(&setup.client.address),
+        6_000_001
+    );
+}
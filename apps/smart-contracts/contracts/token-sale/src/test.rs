@@ -2,7 +2,10 @@
 extern crate std;
 
 use crate::sale::{TokenSaleContract, TokenSaleContractClient};
-use escrow::{Escrow, EscrowContract, EscrowContractClient, Flags, Milestone, Roles, Trustline};
+use escrow::{
+    Appeal, Arbitration, Deadline, Escrow, EscrowContract, EscrowContractClient, Flags, Milestone,
+    MilestoneStatus, Roles, Trustline,
+};
 use soroban_sdk::{testutils::Address as _, token, vec, Address, Env, String};
 use token::Client as TokenClient;
 use token::StellarAssetClient as TokenAdminClient;
@@ -17,7 +20,9 @@ fn create_usdc_token<'a>(e: &Env, admin: &Address) -> (TokenClient<'a>, TokenAdm
 }
 
 fn create_escrow_contract<'a>(env: &Env) -> EscrowContractClient<'a> {
-    EscrowContractClient::new(env, &env.register(EscrowContract {}, ()))
+    let admin = Address::generate(env);
+    let treasury = Address::generate(env);
+    EscrowContractClient::new(env, &env.register(EscrowContract {}, (admin, treasury)))
 }
 
 fn create_token_factory<'a>(e: &Env, admin: &Address) -> FactoryTokenClient<'a> {
@@ -67,6 +72,7 @@ fn test_buy_transfers_usdc_and_mints_sale_token() {
         disputed: false,
         released: false,
         resolved: false,
+        cancelled: false,
     };
 
     let trustline = Trustline {
@@ -80,9 +86,16 @@ fn test_buy_transfers_usdc_and_mints_sale_token() {
         &env,
         Milestone {
             description: String::from_str(&env, "m1"),
-            status: String::from_str(&env, "Pending"),
+            status: MilestoneStatus::Pending,
             evidence: String::from_str(&env, ""),
+            amount,
             approved: false,
+            released: false,
+            rejection_reason: None,
+            rejection_count: 0,
+            disputed: false,
+            resolved: false,
+            depends_on: vec![&env],
         },
     ];
 
@@ -97,6 +110,13 @@ fn test_buy_transfers_usdc_and_mints_sale_token() {
         flags,
         trustline,
         receiver_memo: 0,
+        deadline: Deadline::None,
+        payees: vec![&env],
+        co_approvers: vec![&env],
+        approval_threshold: 1,
+        arbitration: Arbitration::None,
+        appeal: Appeal::None,
+        review_period: None,
     };
 
     escrow_client.initialize_escrow(&escrow_properties);
@@ -105,7 +105,7 @@ impl VaultContract {
             return Err(ContractError::VaultDoesNotHaveEnoughUSDC);
         }
 
-        token_client.transfer(&beneficiary, &env.current_contract_address(), &token_balance);
+        token_client.transfer(&beneficiary, env.current_contract_address(), &token_balance);
 
         usdc_client.transfer(&env.current_contract_address(), &beneficiary, &usdc_amount);
         